@@ -0,0 +1,242 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+	parse_macro_input, spanned::Spanned, Error, FnArg, Ident, ItemFn, Pat, ReturnType, Type,
+};
+
+// Turns a typed async function into one that can be passed to `JunoModule::declare_async_function`.
+// Every argument is deserialized from the call's argument of the same name, and the return
+// value is serialized back. A parameter of type `FunctionContext` receives the call's context.
+// Returning a `Result` fails the call with the error, which has to convert into `juno::Error`.
+// The generated function returns a future, so it can only be declared with
+// `declare_async_function`, and not with `declare_function`.
+// Generic functions aren't supported, since juno can't pick the types to call them with
+#[proc_macro_attribute]
+pub fn function(attr: TokenStream, item: TokenStream) -> TokenStream {
+	let function = parse_macro_input!(item as ItemFn);
+	match expand_function(attr.into(), function) {
+		Ok(tokens) => tokens.into(),
+		Err(err) => err.to_compile_error().into(),
+	}
+}
+
+// Turns a typed function into one that can be passed to `JunoModule::register_async_hook`.
+// It takes at most one parameter: either the `HookEvent` itself, or a type the hook's payload
+// is deserialized into. It returns nothing, or a `Result` whose error converts into `juno::Error`
+#[proc_macro_attribute]
+pub fn hook(attr: TokenStream, item: TokenStream) -> TokenStream {
+	let function = parse_macro_input!(item as ItemFn);
+	match expand_hook(attr.into(), function) {
+		Ok(tokens) => tokens.into(),
+		Err(err) => err.to_compile_error().into(),
+	}
+}
+
+fn expand_function(attr: TokenStream2, function: ItemFn) -> Result<TokenStream2, Error> {
+	ensure_no_arguments(attr)?;
+	if function.sig.asyncness.is_none() {
+		return Err(Error::new(
+			function.sig.fn_token.span(),
+			"#[juno::function] only takes async functions. Use declare_function for the others",
+		));
+	}
+	ensure_not_generic(&function)?;
+	let parameters = parameters_of(&function)?;
+	let (inner_name, inner_function) = inner_function(&function);
+	let ItemFn {
+		attrs, vis, sig, ..
+	} = &function;
+	let name = &sig.ident;
+
+	let mut extractions = vec![];
+	let mut call_arguments = vec![];
+	for (ident, ty) in parameters {
+		if is_type(ty, "FunctionContext") {
+			call_arguments.push(quote! { __juno_context.clone() });
+			continue;
+		}
+		let argument = ident.to_string();
+		extractions.push(quote! {
+			let #ident: #ty = ::juno::macros::extract_argument(&mut __juno_arguments, #argument)?;
+		});
+		call_arguments.push(quote! { #ident });
+	}
+
+	let call = call_inner(&function, &inner_name, &call_arguments);
+	let conversion = if returns_result(&sig.output) {
+		quote! { ::juno::macros::convert_result(#call) }
+	} else {
+		quote! { ::juno::macros::convert_value(#call) }
+	};
+
+	// Without any arguments to take out, the map doesn't need to be mutable
+	let mutability = if extractions.is_empty() {
+		quote! {}
+	} else {
+		quote! { mut }
+	};
+
+	Ok(quote! {
+		#(#attrs)*
+		#vis fn #name(
+			__juno_context: ::juno::functions::FunctionContext,
+			#mutability __juno_arguments: ::std::collections::HashMap<String, ::juno::models::Value>,
+		) -> impl ::std::future::Future<Output = ::juno::Result<::juno::models::Value>> {
+			#inner_function
+			async move {
+				#(#extractions)*
+				#conversion
+			}
+		}
+	})
+}
+
+fn expand_hook(attr: TokenStream2, function: ItemFn) -> Result<TokenStream2, Error> {
+	ensure_no_arguments(attr)?;
+	ensure_not_generic(&function)?;
+	let parameters = parameters_of(&function)?;
+	if parameters.len() > 1 {
+		return Err(Error::new(
+			function.sig.inputs.span(),
+			"#[juno::hook] listeners take at most one parameter",
+		));
+	}
+	let (inner_name, inner_function) = inner_function(&function);
+	let ItemFn {
+		attrs, vis, sig, ..
+	} = &function;
+	let name = &sig.ident;
+
+	let mut extractions = vec![];
+	let mut call_arguments = vec![];
+	if let Some((ident, ty)) = parameters.first() {
+		if is_type(ty, "HookEvent") {
+			call_arguments.push(quote! { __juno_event });
+		} else {
+			extractions.push(quote! {
+				let #ident: #ty = __juno_event.data.deserialize()?;
+			});
+			call_arguments.push(quote! { #ident });
+		}
+	}
+
+	let call = call_inner(&function, &inner_name, &call_arguments);
+	let conversion = match &sig.output {
+		ReturnType::Default => quote! {
+			#call;
+			Ok(())
+		},
+		output if returns_result(output) => quote! {
+			#call.map_err(::std::convert::Into::into)
+		},
+		output => {
+			return Err(Error::new(
+				output.span(),
+				"#[juno::hook] listeners return nothing or a Result",
+			))
+		}
+	};
+
+	Ok(quote! {
+		#(#attrs)*
+		#vis fn #name(
+			__juno_event: ::juno::hooks::HookEvent,
+		) -> impl ::std::future::Future<Output = ::juno::Result<()>> {
+			#inner_function
+			async move {
+				#(#extractions)*
+				#conversion
+			}
+		}
+	})
+}
+
+fn ensure_no_arguments(attr: TokenStream2) -> Result<(), Error> {
+	if attr.is_empty() {
+		Ok(())
+	} else {
+		Err(Error::new(
+			attr.span(),
+			"This attribute doesn't take any arguments",
+		))
+	}
+}
+
+// The generated function has a fixed signature, so there'd be nothing to infer the generics from
+fn ensure_not_generic(function: &ItemFn) -> Result<(), Error> {
+	let generics = &function.sig.generics;
+	if let Some(where_clause) = &generics.where_clause {
+		return Err(Error::new(
+			where_clause.span(),
+			"Juno functions and hooks can't have a where clause",
+		));
+	}
+	if !generics.params.is_empty() {
+		return Err(Error::new(
+			generics.span(),
+			"Juno functions and hooks can't be generic",
+		));
+	}
+	Ok(())
+}
+
+fn parameters_of(function: &ItemFn) -> Result<Vec<(&Ident, &Type)>, Error> {
+	function
+		.sig
+		.inputs
+		.iter()
+		.map(|input| match input {
+			FnArg::Receiver(receiver) => Err(Error::new(
+				receiver.span(),
+				"Methods can't be declared as juno functions or hooks",
+			)),
+			FnArg::Typed(typed) => match &*typed.pat {
+				Pat::Ident(pat) => Ok((&pat.ident, &*typed.ty)),
+				pat => Err(Error::new(
+					pat.span(),
+					"Parameters have to be plain identifiers, to be matched by name",
+				)),
+			},
+		})
+		.collect()
+}
+
+// The original function, nested inside the generated one under a name that can't clash
+fn inner_function(function: &ItemFn) -> (Ident, TokenStream2) {
+	let inner_name = Ident::new("__juno_inner", Span::call_site());
+	let mut inner = function.clone();
+	inner.attrs.clear();
+	inner.vis = syn::Visibility::Inherited;
+	inner.sig.ident = inner_name.clone();
+	(inner_name, quote! { #inner })
+}
+
+fn call_inner(function: &ItemFn, inner_name: &Ident, arguments: &[TokenStream2]) -> TokenStream2 {
+	if function.sig.asyncness.is_some() {
+		quote! { #inner_name(#(#arguments),*).await }
+	} else {
+		quote! { #inner_name(#(#arguments),*) }
+	}
+}
+
+fn returns_result(output: &ReturnType) -> bool {
+	match output {
+		ReturnType::Default => false,
+		ReturnType::Type(_, ty) => is_type(ty, "Result"),
+	}
+}
+
+// Compares the last segment of the type's path, so that the type can be imported or qualified
+fn is_type(ty: &Type, name: &str) -> bool {
+	match ty {
+		Type::Path(path) => path
+			.path
+			.segments
+			.last()
+			.is_some_and(|segment| segment.ident == name),
+		_ => false,
+	}
+}
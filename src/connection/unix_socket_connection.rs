@@ -65,15 +65,18 @@ async fn read_data_from_socket(
 		match read_write_future {
 			Either::Left((read_future_result, next_write_future)) => {
 				// Read event has happened
+				let line = match read_future_result {
+					Some(Ok(line)) => line,
+					// The socket was closed from the other end (or errored out). Stop reading
+					_ => break,
+				};
 				read_future = lines.next();
 				write_future = next_write_future;
 				read_or_write_future = future::select(read_future, write_future);
 				// Send the read data to the MPSC sender
-				if let Some(Ok(line)) = read_future_result {
-					let result = read_sender.send(line.as_bytes().to_vec()).await;
-					if let Err(err) = result {
-						println!("Error queing data from the socket to the module: {}", err);
-					}
+				let result = read_sender.send(line.as_bytes().to_vec()).await;
+				if let Err(err) = result {
+					println!("Error queing data from the socket to the module: {}", err);
 				}
 			}
 			Either::Right((write_future_result, next_read_future)) => {
@@ -91,8 +94,8 @@ async fn read_data_from_socket(
 			}
 		}
 	}
-	// Either a read, nor a write event has happened.
-	// This means the socket close event happened. Shutdown the socket and close any mpsc channels
+	// Either the socket close event happened, or the socket was closed from the other end.
+	// Shutdown the socket and close any mpsc channels
 	drop(lines);
 	let result = read_sender.close().await;
 	if let Err(err) = result {
@@ -129,8 +132,10 @@ impl BaseConnection for UnixSocketConnection {
 			.await;
 		});
 
-		self.connection_setup = true;
-		init_receiver.await.unwrap()
+		// A connection that couldn't be established can be set up again
+		let result = init_receiver.await.unwrap();
+		self.connection_setup = result.is_ok();
+		result
 	}
 
 	async fn close_connection(&mut self) {
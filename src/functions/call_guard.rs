@@ -0,0 +1,56 @@
+use crate::{connection::Buffer, juno_module::ArcRequestList};
+use async_std::task;
+use futures::channel::mpsc::UnboundedSender;
+
+// Cancels a function call when dropped, unless the call was finished first
+pub struct CallGuard {
+	request_id: String,
+	requests: ArcRequestList,
+	write_sender: UnboundedSender<Buffer>,
+	cancel_request: Option<Buffer>,
+}
+
+impl CallGuard {
+	pub fn new(
+		request_id: String,
+		requests: ArcRequestList,
+		write_sender: UnboundedSender<Buffer>,
+		cancel_request: Buffer,
+	) -> Self {
+		CallGuard {
+			request_id,
+			requests,
+			write_sender,
+			cancel_request: Some(cancel_request),
+		}
+	}
+
+	pub fn get_request_id(&self) -> &String {
+		&self.request_id
+	}
+
+	// The call is done. There's nothing left to cancel
+	pub fn finish(&mut self) {
+		self.cancel_request = None;
+	}
+}
+
+impl Drop for CallGuard {
+	fn drop(&mut self) {
+		let cancel_request = match self.cancel_request.take() {
+			Some(cancel_request) => cancel_request,
+			None => return,
+		};
+		if let Err(err) = self.write_sender.unbounded_send(cancel_request) {
+			println!(
+				"Error sending cancel request of requestId {}: {}",
+				self.request_id, err
+			);
+		}
+		let requests = self.requests.clone();
+		let request_id = self.request_id.clone();
+		task::spawn(async move {
+			requests.lock().await.remove(&request_id);
+		});
+	}
+}
@@ -0,0 +1,167 @@
+use crate::{
+	models::Value,
+	utils::{errors, Error, Result},
+};
+use async_std::sync::{Arc, Mutex};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+// When a module's circuit opens, and for how long it stays open
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerPolicy {
+	// Consecutive failed calls to a module before its circuit opens
+	pub failure_threshold: u32,
+	// How long calls fail fast before one is let through to probe the module
+	pub cooldown: Duration,
+}
+
+impl CircuitBreakerPolicy {
+	pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+		CircuitBreakerPolicy {
+			failure_threshold,
+			cooldown,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+	// Calls go through
+	Closed,
+	// Calls fail with Error::CircuitOpen without being sent
+	Open,
+	// A single call is let through. The circuit closes if it succeeds, and opens if it doesn't
+	HalfOpen,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitStateChange {
+	pub module_id: String,
+	pub state: CircuitState,
+}
+
+struct Circuit {
+	state: CircuitState,
+	failures: u32,
+	opened_at: Instant,
+	probe_started_at: Option<Instant>,
+}
+
+// Keeps a circuit per module that's called, keyed by the prefix of the function name.
+// Only failures that say the module is down count: juno not knowing the module (or the module
+// not being registered), timeouts, and calls lost to a disconnect. A module that responds with
+// an error is still up, and so is one that juno says doesn't declare the function.
+// A module that stopped responding only fails through timeouts, which is why the module needs
+// a call timeout to have a circuit breaker
+#[derive(Clone)]
+pub(crate) struct CircuitBreaker {
+	policy: CircuitBreakerPolicy,
+	circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+	observers: Arc<Mutex<Vec<UnboundedSender<CircuitStateChange>>>>,
+}
+
+impl CircuitBreaker {
+	pub fn new(policy: CircuitBreakerPolicy) -> Self {
+		CircuitBreaker {
+			policy,
+			circuits: Arc::new(Mutex::new(HashMap::new())),
+			observers: Arc::new(Mutex::new(vec![])),
+		}
+	}
+
+	pub async fn get_state(&self, module_id: &str) -> CircuitState {
+		match self.circuits.lock().await.get(module_id) {
+			Some(circuit) => circuit.state,
+			None => CircuitState::Closed,
+		}
+	}
+
+	pub async fn observe(&self) -> UnboundedReceiver<CircuitStateChange> {
+		let (sender, receiver) = unbounded::<CircuitStateChange>();
+		self.observers.lock().await.push(sender);
+		receiver
+	}
+
+	// Fails with Error::CircuitOpen if the call shouldn't be sent
+	pub async fn before_call(&self, module_id: &str) -> Result<()> {
+		let mut circuits = self.circuits.lock().await;
+		let circuit = match circuits.get_mut(module_id) {
+			Some(circuit) => circuit,
+			None => return Ok(()),
+		};
+		let now = Instant::now();
+		match circuit.state {
+			CircuitState::Closed => return Ok(()),
+			CircuitState::Open if now.duration_since(circuit.opened_at) >= self.policy.cooldown => {
+				circuit.state = CircuitState::HalfOpen;
+				circuit.probe_started_at = Some(now);
+			}
+			// A probe that was dropped before it finished doesn't hold the circuit up forever
+			CircuitState::HalfOpen
+				if circuit.probe_started_at.is_none_or(|started_at| {
+					now.duration_since(started_at) >= self.policy.cooldown
+				}) =>
+			{
+				circuit.probe_started_at = Some(now);
+				return Ok(());
+			}
+			_ => return Err(Error::CircuitOpen(module_id.to_string())),
+		}
+		drop(circuits);
+		self.notify(module_id, CircuitState::HalfOpen).await;
+		Ok(())
+	}
+
+	pub async fn record(&self, module_id: &str, result: &Result<Value>) {
+		let failed = matches!(
+			result,
+			Err(Error::FromJuno(errors::UNKNOWN_MODULE))
+				| Err(Error::FromJuno(errors::UNREGISTERED_MODULE))
+				| Err(Error::Timeout)
+				| Err(Error::Internal(_))
+		);
+		let mut circuits = self.circuits.lock().await;
+		let circuit = circuits
+			.entry(module_id.to_string())
+			.or_insert_with(|| Circuit {
+				state: CircuitState::Closed,
+				failures: 0,
+				opened_at: Instant::now(),
+				probe_started_at: None,
+			});
+		let previous_state = circuit.state;
+		if failed {
+			circuit.failures += 1;
+			if circuit.state == CircuitState::HalfOpen
+				|| circuit.failures >= self.policy.failure_threshold
+			{
+				circuit.state = CircuitState::Open;
+				circuit.opened_at = Instant::now();
+			}
+		} else {
+			circuit.failures = 0;
+			circuit.state = CircuitState::Closed;
+		}
+		circuit.probe_started_at = None;
+		let state = circuit.state;
+		drop(circuits);
+
+		if state != previous_state {
+			self.notify(module_id, state).await;
+		}
+	}
+
+	async fn notify(&self, module_id: &str, state: CircuitState) {
+		let change = CircuitStateChange {
+			module_id: module_id.to_string(),
+			state,
+		};
+		self.observers
+			.lock()
+			.await
+			.retain(|observer| observer.unbounded_send(change.clone()).is_ok());
+	}
+}
@@ -0,0 +1,82 @@
+use crate::{
+	functions::FunctionCaller,
+	models::Value,
+	utils::{Error, Result},
+};
+use async_std::task;
+use futures::{
+	channel::oneshot::{channel, Receiver, Sender},
+	future::{self, Either},
+};
+use std::{
+	collections::HashMap,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+// A function call that is sent once its delay is up, and resolves to the result of the call.
+// Cancelling or dropping it before the call is sent means it's never sent at all.
+// Afterwards, it cancels the call like a PendingCall would
+pub struct DelayedCall {
+	cancel_sender: Option<Sender<()>>,
+	receiver: Receiver<Result<Value>>,
+}
+
+impl DelayedCall {
+	pub(crate) fn spawn(
+		delay: Duration,
+		fn_name: String,
+		args: HashMap<String, Value>,
+		function_caller: FunctionCaller,
+	) -> Self {
+		let (cancel_sender, cancel_receiver) = channel::<()>();
+		let (sender, receiver) = channel::<Result<Value>>();
+		task::spawn(async move {
+			let sleep = Box::pin(task::sleep(delay));
+			let cancel_receiver = match future::select(sleep, cancel_receiver).await {
+				Either::Left((_, cancel_receiver)) => cancel_receiver,
+				Either::Right(_) => return,
+			};
+
+			let call = Box::pin(function_caller.call(&fn_name, args));
+			let result = match future::select(call, cancel_receiver).await {
+				Either::Left((result, _)) => result,
+				// Dropping the call cancels the attempt that's running, and any retry after it
+				Either::Right(_) => return,
+			};
+			sender.send(result).unwrap_or(());
+		});
+		DelayedCall {
+			cancel_sender: Some(cancel_sender),
+			receiver,
+		}
+	}
+
+	pub fn cancel(mut self) {
+		if let Some(cancel_sender) = self.cancel_sender.take() {
+			cancel_sender.send(()).unwrap_or(());
+		}
+	}
+}
+
+impl Future for DelayedCall {
+	type Output = Result<Value>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		match Pin::new(&mut self.receiver).poll(cx) {
+			Poll::Ready(result) => {
+				// Nothing left to cancel
+				self.cancel_sender = None;
+				Poll::Ready(match result {
+					Ok(value) => value,
+					Err(_) => Err(Error::Internal(String::from(
+						"Request sender was dropped before data could be retrieved",
+					))),
+				})
+			}
+			Poll::Pending => Poll::Pending,
+		}
+	}
+}
@@ -0,0 +1,87 @@
+use crate::{
+	functions::{CircuitBreaker, PendingCall, RetryPolicy},
+	models::Value,
+	utils::{RequestSender, Result},
+};
+use async_std::task;
+use std::{collections::HashMap, time::Duration};
+
+// Makes function calls the way the module is configured to: with its call timeout, through its
+// circuit breaker, and retried by its retry policy. Doesn't borrow the module, so that calls
+// made later on, from a task of their own, go through the same path
+#[derive(Clone)]
+pub(crate) struct FunctionCaller {
+	request_sender: RequestSender,
+	call_timeout: Option<Duration>,
+	retry_policy: Option<RetryPolicy>,
+	circuit_breaker: Option<CircuitBreaker>,
+}
+
+impl FunctionCaller {
+	pub fn new(
+		request_sender: RequestSender,
+		call_timeout: Option<Duration>,
+		retry_policy: Option<RetryPolicy>,
+		circuit_breaker: Option<CircuitBreaker>,
+	) -> Self {
+		FunctionCaller {
+			request_sender,
+			call_timeout,
+			retry_policy,
+			circuit_breaker,
+		}
+	}
+
+	// Sends the call with the call timeout, without going through the circuit breaker
+	pub async fn start(&self, fn_name: &str, args: HashMap<String, Value>) -> Result<PendingCall> {
+		let pending_call = self
+			.request_sender
+			.start_function_call(fn_name.to_string(), args)
+			.await?;
+		Ok(match self.call_timeout {
+			Some(timeout) => pending_call.with_timeout(timeout),
+			None => pending_call,
+		})
+	}
+
+	pub async fn call(&self, fn_name: &str, args: HashMap<String, Value>) -> Result<Value> {
+		match &self.retry_policy {
+			Some(retry_policy) => self.call_with_retry(fn_name, args, retry_policy).await,
+			None => self.call_once(fn_name, args).await,
+		}
+	}
+
+	pub async fn call_with_retry(
+		&self,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+		retry_policy: &RetryPolicy,
+	) -> Result<Value> {
+		let mut attempt = 1;
+		loop {
+			match self.call_once(fn_name, args.clone()).await {
+				Err(err) if retry_policy.should_retry(attempt, &err) => {
+					task::sleep(retry_policy.backoff(attempt)).await;
+					attempt += 1;
+				}
+				result => return result,
+			}
+		}
+	}
+
+	// A single attempt at a call, through the circuit breaker if there is one
+	async fn call_once(&self, fn_name: &str, args: HashMap<String, Value>) -> Result<Value> {
+		let circuit_breaker = match &self.circuit_breaker {
+			Some(circuit_breaker) => circuit_breaker,
+			None => return self.start(fn_name, args).await?.await,
+		};
+		let module_id = fn_name.split('.').next().unwrap_or(fn_name);
+		circuit_breaker.before_call(module_id).await?;
+		let result = match self.start(fn_name, args).await {
+			Ok(pending_call) => pending_call.await,
+			Err(err) => Err(err),
+		};
+		circuit_breaker.record(module_id, &result).await;
+		result
+	}
+}
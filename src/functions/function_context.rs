@@ -0,0 +1,75 @@
+use crate::{connection::Buffer, models::BaseMessage, protocol::BaseProtocol};
+use futures::{
+	channel::{
+		mpsc::UnboundedSender,
+		oneshot::{channel, Receiver, Sender},
+	},
+	future::{self, FutureExt, Shared},
+};
+use std::sync::Arc;
+
+// Passed to async functions, so that they know which call they're serving,
+// can report their progress, and can stop early if the caller cancels the call
+#[derive(Clone)]
+pub struct FunctionContext {
+	request_id: String,
+	function: String,
+	cancel_receiver: Shared<Receiver<()>>,
+	protocol: Arc<BaseProtocol>,
+	write_sender: UnboundedSender<Buffer>,
+}
+
+impl FunctionContext {
+	pub(crate) fn new(
+		request_id: String,
+		function: String,
+		protocol: Arc<BaseProtocol>,
+		write_sender: UnboundedSender<Buffer>,
+	) -> (Self, Sender<()>) {
+		let (cancel_sender, cancel_receiver) = channel::<()>();
+		(
+			FunctionContext {
+				request_id,
+				function,
+				cancel_receiver: cancel_receiver.shared(),
+				protocol,
+				write_sender,
+			},
+			cancel_sender,
+		)
+	}
+
+	pub fn get_request_id(&self) -> &String {
+		&self.request_id
+	}
+
+	pub fn get_function(&self) -> &String {
+		&self.function
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		matches!(self.cancel_receiver.peek(), Some(Ok(())))
+	}
+
+	// Resolves once the caller cancels the call. If the call is never cancelled, this never resolves
+	pub async fn cancelled(&self) {
+		if self.cancel_receiver.clone().await.is_err() {
+			future::pending::<()>().await;
+		}
+	}
+
+	// Sends an intermediate update to the caller. This also pushes back the caller's timeout
+	pub fn report_progress(&self, progress: f64, message: Option<&str>) {
+		if self.is_cancelled() {
+			return;
+		}
+		let write_buffer = self.protocol.encode(BaseMessage::FunctionCallProgress {
+			request_id: self.request_id.clone(),
+			progress,
+			message: message.map(String::from),
+		});
+		if let Err(err) = self.write_sender.unbounded_send(write_buffer) {
+			println!("Error writing progress of function call: {}", err);
+		}
+	}
+}
@@ -0,0 +1,76 @@
+use crate::{
+	functions::{FunctionContext, IncomingCall, Responder},
+	models::Value,
+	utils::Result,
+};
+use futures::{
+	channel::mpsc::UnboundedSender,
+	future::{BoxFuture, FutureExt},
+	stream::{BoxStream, Stream},
+};
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
+
+type AsyncFunction = dyn Fn(FunctionContext, HashMap<String, Value>) -> BoxFuture<'static, Result<Value>>
+	+ Send
+	+ Sync;
+type DeferredFunction = dyn Fn(FunctionContext, HashMap<String, Value>, Responder) + Send + Sync;
+type StreamFunction =
+	dyn Fn(FunctionContext, HashMap<String, Value>) -> BoxStream<'static, Value> + Send + Sync;
+
+// The implementation of a declared function.
+// Each variant matches one of the declare_*_function methods on JunoModule
+#[derive(Clone)]
+pub enum FunctionHandler {
+	Infallible(fn(HashMap<String, Value>) -> Value),
+	Fallible(fn(HashMap<String, Value>) -> Result<Value>),
+	Async(Arc<AsyncFunction>),
+	Stream(Arc<StreamFunction>),
+	// The function answers through the Responder, whenever it's ready to.
+	// Calls that haven't been answered once the timeout elapses are failed with Error::Timeout
+	Deferred {
+		function: Arc<DeferredFunction>,
+		timeout: Option<Duration>,
+	},
+	// Calls are handed over to a stream of IncomingCalls, to be responded to by whoever reads them
+	Incoming(UnboundedSender<IncomingCall>),
+}
+
+impl FunctionHandler {
+	pub fn from_async<F, Fut>(function: F) -> Self
+	where
+		F: Fn(FunctionContext, HashMap<String, Value>) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<Value>> + Send + 'static,
+	{
+		FunctionHandler::Async(Arc::new(move |context, args| {
+			function(context, args).boxed()
+		}))
+	}
+
+	// Whether calls to the function run on their own task, rather than on the data listener
+	pub(crate) fn runs_on_task(&self) -> bool {
+		!matches!(
+			self,
+			FunctionHandler::Infallible(_) | FunctionHandler::Fallible(_)
+		)
+	}
+
+	pub fn from_deferred<F>(timeout: Option<Duration>, function: F) -> Self
+	where
+		F: Fn(FunctionContext, HashMap<String, Value>, Responder) + Send + Sync + 'static,
+	{
+		FunctionHandler::Deferred {
+			function: Arc::new(function),
+			timeout,
+		}
+	}
+
+	pub fn from_stream<F, S>(function: F) -> Self
+	where
+		F: Fn(FunctionContext, HashMap<String, Value>) -> S + Send + Sync + 'static,
+		S: Stream<Item = Value> + Send + 'static,
+	{
+		FunctionHandler::Stream(Arc::new(
+			move |context, args| -> BoxStream<'static, Value> { Box::pin(function(context, args)) },
+		))
+	}
+}
@@ -0,0 +1,6 @@
+// An intermediate update sent by a function before its final response
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProgress {
+	pub progress: f64,
+	pub message: Option<String>,
+}
@@ -0,0 +1,68 @@
+use crate::{
+	connection::Buffer,
+	juno_module::ArcRunningCallList,
+	models::{BaseMessage, ErrorSource, Value},
+	protocol::BaseProtocol,
+	utils::{Error, Result},
+};
+use futures::{channel::mpsc::UnboundedSender, sink::SinkExt};
+
+// Responds to a call that was running on its own task.
+// If the call isn't running anymore, it was cancelled, and nobody is waiting for the response
+pub async fn finish_running_call(
+	protocol: &BaseProtocol,
+	write_sender: &mut UnboundedSender<Buffer>,
+	running_calls: &ArcRunningCallList,
+	request_id: String,
+	result: Result<Value>,
+) {
+	if running_calls.lock().await.remove(&request_id).is_none() {
+		return;
+	}
+	send_function_response(protocol, write_sender, request_id, result).await;
+}
+
+pub async fn send_function_response(
+	protocol: &BaseProtocol,
+	write_sender: &mut UnboundedSender<Buffer>,
+	request_id: String,
+	result: Result<Value>,
+) {
+	let write_buffer = match result {
+		Ok(value) => protocol.encode(BaseMessage::FunctionCallResponse {
+			request_id,
+			data: value,
+		}),
+		Err(error) => protocol.encode(error_response(request_id, error)),
+	};
+	if let Err(err) = write_sender.send(write_buffer).await {
+		println!("Error writing back result of function call: {}", err);
+	}
+}
+
+pub fn error_response(request_id: String, error: Error) -> BaseMessage {
+	let (error, message, details, source) = match error {
+		// Passed along as juno's own, so that the caller handles it as if juno had answered
+		Error::FromJuno(code) => (code, None, Value::Null, ErrorSource::Juno),
+		Error::FromModule {
+			code,
+			message,
+			details,
+		} => (code, message, details, ErrorSource::Module),
+		// The rest only mean something within this module. They go out with codes of their own,
+		// so that the caller can't mistake them for its own timeouts, disconnects and such
+		error => (
+			error.code(),
+			Some(error.to_string()),
+			Value::Null,
+			ErrorSource::Module,
+		),
+	};
+	BaseMessage::Error {
+		request_id,
+		error,
+		message,
+		details,
+		source,
+	}
+}
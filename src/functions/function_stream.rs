@@ -0,0 +1,43 @@
+use crate::{functions::call_guard::CallGuard, models::Value, utils::Result};
+use futures::{channel::mpsc::UnboundedReceiver, stream::Stream};
+use std::{
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+// The chunks of a streaming function call, as they arrive. Chunks that arrive faster than
+// they are read are buffered without a limit.
+// Dropping it before the stream ends cancels the call
+pub struct FunctionStream {
+	guard: CallGuard,
+	receiver: UnboundedReceiver<Result<Value>>,
+}
+
+impl FunctionStream {
+	pub(crate) fn new(guard: CallGuard, receiver: UnboundedReceiver<Result<Value>>) -> Self {
+		FunctionStream { guard, receiver }
+	}
+
+	pub fn get_request_id(&self) -> &String {
+		self.guard.get_request_id()
+	}
+
+	pub fn cancel(self) {
+		drop(self);
+	}
+}
+
+impl Stream for FunctionStream {
+	type Item = Result<Value>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let item = match Pin::new(&mut self.receiver).poll_next(cx) {
+			Poll::Ready(item) => item,
+			Poll::Pending => return Poll::Pending,
+		};
+		if item.is_none() {
+			self.guard.finish();
+		}
+		Poll::Ready(item)
+	}
+}
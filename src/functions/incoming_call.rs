@@ -0,0 +1,50 @@
+use crate::{
+	functions::{FunctionContext, Responder},
+	models::Value,
+	utils::Error,
+};
+use std::collections::HashMap;
+
+// A call to a function declared with incoming_calls, waiting to be responded to.
+// It can be moved to whichever task or worker ends up handling it.
+// Dropping it without responding fails the call
+pub struct IncomingCall {
+	context: FunctionContext,
+	arguments: HashMap<String, Value>,
+	responder: Responder,
+}
+
+impl IncomingCall {
+	pub(crate) fn new(
+		context: FunctionContext,
+		arguments: HashMap<String, Value>,
+		responder: Responder,
+	) -> Self {
+		IncomingCall {
+			context,
+			arguments,
+			responder,
+		}
+	}
+
+	pub fn get_context(&self) -> &FunctionContext {
+		&self.context
+	}
+
+	pub fn get_arguments(&self) -> &HashMap<String, Value> {
+		&self.arguments
+	}
+
+	// Splits the call up, for when the arguments and the responder go separate ways
+	pub fn into_parts(self) -> (FunctionContext, HashMap<String, Value>, Responder) {
+		(self.context, self.arguments, self.responder)
+	}
+
+	pub async fn respond(self, value: Value) {
+		self.responder.respond(value).await;
+	}
+
+	pub async fn fail(self, error: Error) {
+		self.responder.fail(error).await;
+	}
+}
@@ -0,0 +1,48 @@
+use crate::{
+	functions::{FunctionContext, FunctionHandler},
+	models::Value,
+	utils::Result,
+};
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+// A stateful object exposing several functions, which are declared together under its prefix.
+// Every function gets its own handle to the service, so the state lives on the service itself
+pub trait JunoService: Send + Sync + Sized + 'static {
+	// The functions are declared as `{prefix}.{name}`
+	fn prefix(&self) -> &str;
+
+	fn declare_functions(functions: &mut ServiceFunctions<Self>);
+}
+
+// Collects the functions of a service while it's being registered
+pub struct ServiceFunctions<S: JunoService> {
+	service: Arc<S>,
+	handlers: Vec<(String, FunctionHandler)>,
+}
+
+impl<S: JunoService> ServiceFunctions<S> {
+	pub(crate) fn new(service: Arc<S>) -> Self {
+		ServiceFunctions {
+			service,
+			handlers: vec![],
+		}
+	}
+
+	pub fn function<F, Fut>(&mut self, name: &str, function: F) -> &mut Self
+	where
+		F: Fn(Arc<S>, FunctionContext, HashMap<String, Value>) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<Value>> + Send + 'static,
+	{
+		let service = self.service.clone();
+		let handler = FunctionHandler::from_async(move |context, args| {
+			function(service.clone(), context, args)
+		});
+		self.handlers
+			.push((format!("{}.{}", self.service.prefix(), name), handler));
+		self
+	}
+
+	pub(crate) fn into_handlers(self) -> Vec<(String, FunctionHandler)> {
+		self.handlers
+	}
+}
@@ -0,0 +1,36 @@
+mod call_guard;
+mod circuit_breaker;
+mod delayed_call;
+mod function_caller;
+mod function_context;
+mod function_handler;
+mod function_progress;
+mod function_response;
+mod function_stream;
+mod incoming_call;
+mod juno_service;
+mod module_availability;
+mod module_directory;
+mod pending_call;
+mod pending_request;
+mod responder;
+mod retry_policy;
+
+pub(crate) use call_guard::CallGuard;
+pub(crate) use circuit_breaker::CircuitBreaker;
+pub use circuit_breaker::{CircuitBreakerPolicy, CircuitState, CircuitStateChange};
+pub use delayed_call::DelayedCall;
+pub(crate) use function_caller::FunctionCaller;
+pub use function_context::FunctionContext;
+pub use function_handler::FunctionHandler;
+pub use function_progress::FunctionProgress;
+pub(crate) use function_response::{finish_running_call, send_function_response};
+pub use function_stream::FunctionStream;
+pub use incoming_call::IncomingCall;
+pub use juno_service::{JunoService, ServiceFunctions};
+pub use module_availability::{ModuleAvailability, ModuleStatus};
+pub(crate) use module_directory::{ModuleDirectory, DISCOVERY_HOOK};
+pub use pending_call::PendingCall;
+pub(crate) use pending_request::PendingRequest;
+pub use responder::Responder;
+pub use retry_policy::RetryPolicy;
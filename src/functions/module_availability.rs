@@ -0,0 +1,92 @@
+use crate::utils::{errors, Error, RequestSender};
+use async_std::task;
+use futures::{
+	channel::mpsc::{unbounded, UnboundedReceiver},
+	stream::Stream,
+};
+use std::{
+	collections::HashMap,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+// Nothing declares this, so a module that's around answers with UNKNOWN_FUNCTION,
+// while juno answers with UNKNOWN_MODULE for one that isn't
+const PROBE_FUNCTION: &str = "__juno_probe";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModuleStatus {
+	Available,
+	Unavailable,
+}
+
+// Whether a module is registered with juno, as it changes.
+// Juno doesn't tell other modules when one comes or goes, so the module is probed with a
+// function call every interval. The first item is the status found by the first probe,
+// and after that, an item is only sent when the status changes.
+// The stream ends once the connection to juno is closed. Dropping it stops the probing
+pub struct ModuleAvailability {
+	receiver: UnboundedReceiver<ModuleStatus>,
+}
+
+impl ModuleAvailability {
+	pub(crate) fn spawn(
+		module_id: String,
+		interval: Duration,
+		request_sender: RequestSender,
+	) -> Self {
+		let (sender, receiver) = unbounded::<ModuleStatus>();
+		task::spawn(async move {
+			let mut last_status = None;
+			while !sender.is_closed() {
+				let status = probe(&request_sender, &module_id, interval).await;
+				// Nothing can be probed without a connection, so there's nothing left to follow
+				let connection_closed = status.is_none();
+				let status = status.unwrap_or(ModuleStatus::Unavailable);
+				if last_status != Some(status) {
+					last_status = Some(status);
+					if sender.unbounded_send(status).is_err() {
+						return;
+					}
+				}
+				if connection_closed {
+					return;
+				}
+				task::sleep(interval).await;
+			}
+		});
+		ModuleAvailability { receiver }
+	}
+}
+
+impl Stream for ModuleAvailability {
+	type Item = ModuleStatus;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Pin::new(&mut self.receiver).poll_next(cx)
+	}
+}
+
+// A probe that isn't answered within the interval counts as the module being unavailable.
+// Returns None if the probe couldn't be sent at all, or was lost to a disconnect
+async fn probe(
+	request_sender: &RequestSender,
+	module_id: &str,
+	timeout: Duration,
+) -> Option<ModuleStatus> {
+	let call = request_sender
+		.start_function_call(format!("{}.{}", module_id, PROBE_FUNCTION), HashMap::new())
+		.await;
+	let result = match call {
+		Ok(pending_call) => pending_call.with_timeout(timeout).await,
+		Err(err) => Err(err),
+	};
+	match result {
+		Err(Error::Internal(_)) => None,
+		Err(Error::FromJuno(errors::UNKNOWN_MODULE)) | Err(Error::Timeout) => {
+			Some(ModuleStatus::Unavailable)
+		}
+		_ => Some(ModuleStatus::Available),
+	}
+}
@@ -0,0 +1,84 @@
+use crate::{
+	hooks::{HookEvent, HookListener},
+	models::Value,
+	utils::RequestSender,
+};
+use async_std::sync::{Arc, Mutex};
+use std::collections::{BTreeSet, HashMap};
+
+// Juno can't list the modules connected to it, so every module announces itself on this hook
+// once it's registered, and answers the announcements of the modules that register after it
+pub const DISCOVERY_HOOK: &str = "__juno.modules";
+
+const MODULE_ID: &str = "moduleId";
+const IS_ANSWER: &str = "isAnswer";
+
+// The other modules this one has heard of through the discovery hook.
+// A module that has since disconnected stays in it, juno tells as much when it's called
+#[derive(Clone, Default)]
+pub(crate) struct ModuleDirectory {
+	modules: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl ModuleDirectory {
+	pub fn new() -> Self {
+		ModuleDirectory::default()
+	}
+
+	// What the module announces itself with on the discovery hook
+	pub fn announcement(module_id: &str, is_answer: bool) -> Value {
+		let mut announcement = HashMap::new();
+		announcement.insert(
+			String::from(MODULE_ID),
+			Value::String(module_id.to_string()),
+		);
+		announcement.insert(String::from(IS_ANSWER), Value::Bool(is_answer));
+		Value::Object(announcement)
+	}
+
+	// Records the modules that announce themselves, and answers the ones that just registered,
+	// so that they hear of this module as well
+	pub fn listener(&self, request_sender: RequestSender) -> HookListener {
+		let directory = self.clone();
+		HookListener::Async {
+			id: HookListener::next_id(),
+			listener: Arc::new(move |event: HookEvent| {
+				let directory = directory.clone();
+				let request_sender = request_sender.clone();
+				Box::pin(async move {
+					let announcement = match event.data.as_object() {
+						Some(announcement) => announcement,
+						None => return Ok(()),
+					};
+					let module_id = match announcement.get(MODULE_ID).and_then(Value::as_string) {
+						Some(module_id) => module_id,
+						None => return Ok(()),
+					};
+					let protocol = request_sender.get_protocol();
+					if module_id == protocol.get_module_id() {
+						return Ok(());
+					}
+					directory.modules.lock().await.insert(module_id.clone());
+
+					let is_answer = announcement
+						.get(IS_ANSWER)
+						.and_then(Value::as_bool)
+						.copied()
+						.unwrap_or(false);
+					if is_answer {
+						return Ok(());
+					}
+					let answer = ModuleDirectory::announcement(protocol.get_module_id(), true);
+					let request = protocol.trigger_hook(String::from(DISCOVERY_HOOK), answer);
+					request_sender.send_request(request).await?;
+					Ok(())
+				})
+			}),
+		}
+	}
+
+	// The modules heard of so far, ordered by module id
+	pub async fn modules(&self) -> Vec<String> {
+		self.modules.lock().await.iter().cloned().collect()
+	}
+}
@@ -0,0 +1,106 @@
+use crate::{
+	functions::{call_guard::CallGuard, FunctionProgress},
+	models::Value,
+	utils::{Error, Result},
+};
+use async_std::task;
+use futures::{
+	channel::{mpsc::UnboundedReceiver, oneshot::Receiver},
+	future::BoxFuture,
+	stream::Stream,
+};
+use std::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+type ProgressCallback = Box<dyn FnMut(FunctionProgress) + Send>;
+
+// A function call that has been sent, but hasn't been responded to yet.
+// Dropping it before it resolves cancels the call
+pub struct PendingCall {
+	guard: CallGuard,
+	receiver: Receiver<Result<Value>>,
+	progress_receiver: UnboundedReceiver<FunctionProgress>,
+	progress_callback: Option<ProgressCallback>,
+	timeout: Option<Duration>,
+	timer: Option<BoxFuture<'static, ()>>,
+}
+
+impl PendingCall {
+	pub(crate) fn new(
+		guard: CallGuard,
+		receiver: Receiver<Result<Value>>,
+		progress_receiver: UnboundedReceiver<FunctionProgress>,
+	) -> Self {
+		PendingCall {
+			guard,
+			receiver,
+			progress_receiver,
+			progress_callback: None,
+			timeout: None,
+			timer: None,
+		}
+	}
+
+	pub fn get_request_id(&self) -> &String {
+		self.guard.get_request_id()
+	}
+
+	// Called with every progress update the function sends, while the call is being awaited
+	pub fn on_progress<F>(mut self, callback: F) -> Self
+	where
+		F: FnMut(FunctionProgress) + Send + 'static,
+	{
+		self.progress_callback = Some(Box::new(callback));
+		self
+	}
+
+	// Fails the call with Error::Timeout if the function doesn't respond in time.
+	// Every progress update from the function starts the timeout over
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self.timer = Some(Box::pin(task::sleep(timeout)));
+		self
+	}
+
+	pub fn cancel(self) {
+		drop(self);
+	}
+}
+
+impl Future for PendingCall {
+	type Output = Result<Value>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		while let Poll::Ready(Some(progress)) = Pin::new(&mut self.progress_receiver).poll_next(cx)
+		{
+			if let Some(timeout) = self.timeout {
+				self.timer = Some(Box::pin(task::sleep(timeout)));
+			}
+			if let Some(callback) = self.progress_callback.as_mut() {
+				callback(progress);
+			}
+		}
+
+		if let Poll::Ready(result) = Pin::new(&mut self.receiver).poll(cx) {
+			self.guard.finish();
+			return Poll::Ready(match result {
+				Ok(value) => value,
+				Err(_) => Err(Error::Internal(String::from(
+					"Request sender was dropped before data could be retrieved",
+				))),
+			});
+		}
+
+		// The call stays unfinished, so it gets cancelled once this is dropped
+		if let Some(timer) = self.timer.as_mut() {
+			if timer.as_mut().poll(cx).is_ready() {
+				return Poll::Ready(Err(Error::Timeout));
+			}
+		}
+		Poll::Pending
+	}
+}
@@ -0,0 +1,76 @@
+use crate::{functions::FunctionProgress, models::Value, utils::Result};
+use futures::channel::{mpsc::UnboundedSender, oneshot::Sender};
+
+// A request that is waiting for a response from juno
+pub enum PendingRequest {
+	// Resolves once with the response. If the function streams its response,
+	// the chunks are collected into an array, so the whole response is held in memory
+	Single {
+		sender: Sender<Result<Value>>,
+		chunks: Vec<Value>,
+		progress_sender: Option<UnboundedSender<FunctionProgress>>,
+	},
+	// Forwards every chunk as it arrives, until the stream ends
+	Stream(UnboundedSender<Result<Value>>),
+}
+
+impl PendingRequest {
+	pub fn single(sender: Sender<Result<Value>>) -> Self {
+		PendingRequest::Single {
+			sender,
+			chunks: vec![],
+			progress_sender: None,
+		}
+	}
+
+	pub fn function_call(
+		sender: Sender<Result<Value>>,
+		progress_sender: UnboundedSender<FunctionProgress>,
+	) -> Self {
+		PendingRequest::Single {
+			sender,
+			chunks: vec![],
+			progress_sender: Some(progress_sender),
+		}
+	}
+
+	pub fn push_progress(&self, progress: FunctionProgress) {
+		if let PendingRequest::Single {
+			progress_sender: Some(progress_sender),
+			..
+		} = self
+		{
+			progress_sender.unbounded_send(progress).unwrap_or(());
+		}
+	}
+
+	pub fn push_chunk(&mut self, data: Value) {
+		match self {
+			PendingRequest::Single { chunks, .. } => chunks.push(data),
+			PendingRequest::Stream(sender) => {
+				// The stream was dropped. The cancel request is already on its way
+				sender.unbounded_send(Ok(data)).unwrap_or(());
+			}
+		}
+	}
+
+	pub fn end_stream(self) {
+		match self {
+			PendingRequest::Single { sender, chunks, .. } => {
+				sender.send(Ok(Value::Array(chunks))).unwrap_or(());
+			}
+			PendingRequest::Stream(_) => {
+				// Dropping the sender ends the stream
+			}
+		}
+	}
+
+	pub fn resolve(self, value: Result<Value>) -> std::result::Result<(), Result<Value>> {
+		match self {
+			PendingRequest::Single { sender, .. } => sender.send(value),
+			PendingRequest::Stream(sender) => {
+				sender.unbounded_send(value).map_err(|err| err.into_inner())
+			}
+		}
+	}
+}
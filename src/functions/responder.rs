@@ -0,0 +1,117 @@
+use crate::{
+	connection::Buffer,
+	functions::function_response::finish_running_call,
+	juno_module::ArcRunningCallList,
+	models::Value,
+	protocol::BaseProtocol,
+	utils::{Error, Result},
+};
+use async_std::task;
+use futures::channel::mpsc::UnboundedSender;
+use std::{sync::Arc, time::Duration};
+
+// Answers a function call, whenever the answer is ready. It can be moved to another task or
+// stored until then. Only the first answer counts: once the call times out, it's failed with
+// Error::Timeout, and a responder dropped without answering fails it right away
+pub struct Responder {
+	request_id: String,
+	protocol: Arc<BaseProtocol>,
+	write_sender: UnboundedSender<Buffer>,
+	running_calls: ArcRunningCallList,
+	responded: bool,
+}
+
+impl Responder {
+	pub(crate) fn new(
+		request_id: String,
+		protocol: Arc<BaseProtocol>,
+		write_sender: UnboundedSender<Buffer>,
+		running_calls: ArcRunningCallList,
+		timeout: Option<Duration>,
+	) -> Self {
+		let responder = Responder {
+			request_id,
+			protocol,
+			write_sender,
+			running_calls,
+			responded: false,
+		};
+		if let Some(timeout) = timeout {
+			let (request_id, protocol, mut write_sender, running_calls) = responder.parts();
+			task::spawn(async move {
+				task::sleep(timeout).await;
+				finish_running_call(
+					&protocol,
+					&mut write_sender,
+					&running_calls,
+					request_id,
+					Err(Error::Timeout),
+				)
+				.await;
+			});
+		}
+		responder
+	}
+
+	pub fn get_request_id(&self) -> &String {
+		&self.request_id
+	}
+
+	pub async fn respond(self, value: Value) {
+		self.finish(Ok(value)).await;
+	}
+
+	pub async fn fail(self, error: Error) {
+		self.finish(Err(error)).await;
+	}
+
+	async fn finish(mut self, result: Result<Value>) {
+		self.responded = true;
+		let (request_id, protocol, mut write_sender, running_calls) = self.parts();
+		finish_running_call(
+			&protocol,
+			&mut write_sender,
+			&running_calls,
+			request_id,
+			result,
+		)
+		.await;
+	}
+
+	fn parts(
+		&self,
+	) -> (
+		String,
+		Arc<BaseProtocol>,
+		UnboundedSender<Buffer>,
+		ArcRunningCallList,
+	) {
+		(
+			self.request_id.clone(),
+			self.protocol.clone(),
+			self.write_sender.clone(),
+			self.running_calls.clone(),
+		)
+	}
+}
+
+impl Drop for Responder {
+	fn drop(&mut self) {
+		if self.responded {
+			return;
+		}
+		let (request_id, protocol, mut write_sender, running_calls) = self.parts();
+		task::spawn(async move {
+			finish_running_call(
+				&protocol,
+				&mut write_sender,
+				&running_calls,
+				request_id,
+				Err(Error::Internal(String::from(
+					"Responder was dropped without responding",
+				))),
+			)
+			.await;
+		});
+	}
+}
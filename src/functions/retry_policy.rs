@@ -0,0 +1,90 @@
+use crate::utils::{errors, Error};
+use std::{
+	collections::hash_map::RandomState,
+	hash::{BuildHasher, Hasher},
+	time::Duration,
+};
+
+// How a function call is retried when it fails.
+// Calls that juno couldn't route with one of the retryable codes are retried, and so are calls
+// that were lost to a disconnect. Errors from the function itself, and calls that timed out,
+// are only retried for idempotent functions, since they might've run already
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+	// Including the first one
+	pub max_attempts: u32,
+	pub initial_backoff: Duration,
+	pub max_backoff: Duration,
+	// Waits a random time between half the backoff and the full backoff,
+	// so that callers that failed together don't retry together
+	pub jitter: bool,
+	pub retryable_codes: Vec<u32>,
+	pub idempotent: bool,
+}
+
+impl RetryPolicy {
+	// Calls once, and fails right away
+	pub fn never() -> Self {
+		RetryPolicy {
+			max_attempts: 1,
+			initial_backoff: Duration::from_secs(0),
+			max_backoff: Duration::from_secs(0),
+			jitter: false,
+			retryable_codes: vec![],
+			idempotent: false,
+		}
+	}
+
+	// Doubles the backoff after every attempt, up to 30 seconds, with jitter.
+	// Retries calls to modules that juno doesn't know about (yet)
+	pub fn exponential(max_attempts: u32, initial_backoff: Duration) -> Self {
+		RetryPolicy {
+			max_attempts,
+			initial_backoff,
+			max_backoff: Duration::from_secs(30),
+			jitter: true,
+			retryable_codes: vec![errors::UNKNOWN_MODULE],
+			idempotent: false,
+		}
+	}
+
+	pub fn idempotent(mut self) -> Self {
+		self.idempotent = true;
+		self
+	}
+
+	pub(crate) fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+		if attempt >= self.max_attempts {
+			return false;
+		}
+		match error {
+			Error::FromJuno(code) => self.retryable_codes.contains(code),
+			// The connection went away before the call was answered
+			Error::Internal(_) => true,
+			Error::FromModule { .. } | Error::Timeout => self.idempotent,
+			Error::Configuration(_) | Error::Registration { .. } | Error::CircuitOpen(_) => false,
+		}
+	}
+
+	// How long to wait after the given attempt failed
+	pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+		let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+		let backoff = self
+			.initial_backoff
+			.checked_mul(factor)
+			.map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+		if !self.jitter {
+			return backoff;
+		}
+		let mut hasher = RandomState::new().build_hasher();
+		hasher.write_u32(attempt);
+		let fraction = (hasher.finish() % 1000) as u32;
+		backoff / 2 + backoff / 2 * fraction / 1000
+	}
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self::never()
+	}
+}
@@ -0,0 +1,139 @@
+use crate::{
+	hooks::{hook_matches, HookEvent, HookListener},
+	juno_module::{ArcHookHistory, ArcHookListenerList},
+	models::Value,
+	utils::Error,
+};
+use async_std::{prelude::*, task};
+use futures::{
+	channel::mpsc::{unbounded, UnboundedSender},
+	future::FutureExt,
+};
+use std::{panic::AssertUnwindSafe, sync::Arc};
+
+// Receives the errors of hook listeners, along with the hook they were listening to
+pub(crate) type HookErrorSink = Arc<dyn Fn(&str, Error) + Send + Sync>;
+
+// How the listeners of a triggered hook are run
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HookExecution {
+	// One after the other, in the order they were registered.
+	// Hooks are also handled in the order they were triggered
+	#[default]
+	Sequential,
+	// All at once, each on its own task
+	Concurrent,
+}
+
+// Runs hook listeners away from the data listener, so that a slow, failing,
+// or panicking listener can't hold up the module or the other listeners
+pub struct HookDispatcher {
+	hook_listeners: ArcHookListenerList,
+	hook_history: ArcHookHistory,
+	error_sink: HookErrorSink,
+	sequential_sender: Option<UnboundedSender<(HookEvent, Vec<HookListener>)>>,
+}
+
+impl HookDispatcher {
+	pub fn new(
+		hook_listeners: ArcHookListenerList,
+		hook_history: ArcHookHistory,
+		execution: HookExecution,
+		error_sink: HookErrorSink,
+	) -> Self {
+		if execution == HookExecution::Concurrent {
+			return HookDispatcher {
+				hook_listeners,
+				hook_history,
+				error_sink,
+				sequential_sender: None,
+			};
+		}
+
+		let (sender, mut receiver) = unbounded::<(HookEvent, Vec<HookListener>)>();
+		let loop_error_sink = error_sink.clone();
+		task::spawn(async move {
+			while let Some((event, listeners)) = receiver.next().await {
+				for listener in listeners {
+					run_listener(listener, event.clone(), &loop_error_sink).await;
+				}
+			}
+		});
+		HookDispatcher {
+			hook_listeners,
+			hook_history,
+			error_sink,
+			sequential_sender: Some(sender),
+		}
+	}
+
+	pub async fn trigger(&self, hook: String, data: Value) {
+		// The listeners run without holding the lock, so they're free to (un)register hooks
+		let hook_listeners = self.hook_listeners.lock().await;
+		let event = HookEvent { hook, data };
+		// Recorded while the listeners are locked, so that a subscriber being added either
+		// replays this event or receives it, but never both
+		self.hook_history.lock().await.record(&event);
+
+		let hook = &event.hook;
+		let mut matched = false;
+		let mut listeners = vec![];
+		for (pattern, pattern_listeners) in hook_listeners.iter() {
+			if !hook_matches(pattern, hook) {
+				continue;
+			}
+			matched = true;
+			listeners.extend(pattern_listeners.iter().cloned());
+		}
+		drop(hook_listeners);
+
+		// The last listener of this hook might have just unregistered
+		if !matched {
+			println!("Received hook {} without any listeners. Ignoring it", hook);
+			return;
+		}
+		self.dispatch(event, listeners);
+	}
+
+	fn dispatch(&self, event: HookEvent, listeners: Vec<HookListener>) {
+		// Subscriptions only queue the event, so they're notified right away to keep it in order.
+		// Retentions only need the event recorded, which it already is
+		let listeners = listeners
+			.into_iter()
+			.filter(|listener| !listener.queue(&event))
+			.collect::<Vec<_>>();
+		if listeners.is_empty() {
+			return;
+		}
+
+		match &self.sequential_sender {
+			Some(sender) => {
+				if let Err(err) = sender.unbounded_send((event, listeners)) {
+					println!("Error queuing hook for its listeners: {}", err);
+				}
+			}
+			None => {
+				for listener in listeners {
+					let event = event.clone();
+					let error_sink = self.error_sink.clone();
+					task::spawn(async move { run_listener(listener, event, &error_sink).await });
+				}
+			}
+		}
+	}
+}
+
+async fn run_listener(listener: HookListener, event: HookEvent, error_sink: &HookErrorSink) {
+	let hook = event.hook.clone();
+	match AssertUnwindSafe(listener.notify(event))
+		.catch_unwind()
+		.await
+	{
+		Ok(Ok(())) => {}
+		Ok(Err(err)) => error_sink(&hook, err),
+		Err(_) => error_sink(
+			&hook,
+			Error::Internal(String::from("Hook listener panicked")),
+		),
+	}
+}
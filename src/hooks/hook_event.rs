@@ -0,0 +1,8 @@
+use crate::models::Value;
+
+// Sent to hook subscribers every time the hook is triggered
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookEvent {
+	pub hook: String,
+	pub data: Value,
+}
@@ -0,0 +1,142 @@
+use crate::hooks::{hook_matches, HookEvent};
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, Instant},
+};
+
+// How many events of a hook the module keeps around, and for how long
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HookRetention {
+	pub size: usize,
+	pub ttl: Option<Duration>,
+}
+
+impl HookRetention {
+	pub fn new(size: usize) -> Self {
+		HookRetention { size, ttl: None }
+	}
+
+	// Only the latest event of each hook, like a retained message in MQTT
+	pub fn latest() -> Self {
+		Self::new(1)
+	}
+
+	pub fn with_ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = Some(ttl);
+		self
+	}
+}
+
+// Which of the retained events a new subscriber receives before the live ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookReplay {
+	// The latest event of every hook that matches the subscription
+	Latest,
+	// The last N events across every hook that matches the subscription
+	Last(usize),
+	All,
+}
+
+struct RetainedEvent {
+	received_at: Instant,
+	event: HookEvent,
+}
+
+// The recent events of every hook that has a retention, oldest first
+#[derive(Default)]
+pub struct HookHistory {
+	retentions: HashMap<String, HookRetention>,
+	events: HashMap<String, VecDeque<RetainedEvent>>,
+}
+
+impl HookHistory {
+	// The hook can also be a pattern, in which case every hook matching it is retained
+	pub fn retain(&mut self, hook: String, retention: HookRetention) {
+		self.retentions.insert(hook, retention);
+		let retentions = &self.retentions;
+		for (hook, events) in self.events.iter_mut() {
+			trim(events, find_retention(retentions, hook).unwrap());
+		}
+	}
+
+	// Forgets the retention, along with the events of hooks that aren't retained anymore
+	pub fn release(&mut self, hook: &str) {
+		self.retentions.remove(hook);
+		let retentions = &self.retentions;
+		self.events
+			.retain(|hook, events| match find_retention(retentions, hook) {
+				Some(retention) => {
+					trim(events, retention);
+					true
+				}
+				None => false,
+			});
+	}
+
+	pub fn record(&mut self, event: &HookEvent) {
+		let retention = match find_retention(&self.retentions, &event.hook) {
+			Some(retention) => retention,
+			None => return,
+		};
+		let events = self.events.entry(event.hook.clone()).or_default();
+		events.push_back(RetainedEvent {
+			received_at: Instant::now(),
+			event: event.clone(),
+		});
+		trim(events, retention);
+	}
+
+	pub fn replay(&mut self, pattern: &str, replay: HookReplay) -> Vec<HookEvent> {
+		let retentions = &self.retentions;
+		let mut replayed = vec![];
+		for (hook, events) in self.events.iter_mut() {
+			if !hook_matches(pattern, hook) {
+				continue;
+			}
+			trim(events, find_retention(retentions, hook).unwrap());
+			match replay {
+				HookReplay::Latest => replayed.extend(events.back()),
+				_ => replayed.extend(events.iter()),
+			}
+		}
+		replayed.sort_by_key(|retained| retained.received_at);
+
+		let skipped = match replay {
+			HookReplay::Last(count) => replayed.len().saturating_sub(count),
+			_ => 0,
+		};
+		replayed
+			.into_iter()
+			.skip(skipped)
+			.map(|retained| retained.event.clone())
+			.collect()
+	}
+}
+
+// An exact match takes precedence over a pattern
+fn find_retention(
+	retentions: &HashMap<String, HookRetention>,
+	hook: &str,
+) -> Option<HookRetention> {
+	if let Some(retention) = retentions.get(hook) {
+		return Some(*retention);
+	}
+	retentions
+		.iter()
+		.find(|(pattern, _)| hook_matches(pattern, hook))
+		.map(|(_, retention)| *retention)
+}
+
+fn trim(events: &mut VecDeque<RetainedEvent>, retention: HookRetention) {
+	while events.len() > retention.size {
+		events.pop_front();
+	}
+	if let Some(ttl) = retention.ttl {
+		while let Some(retained) = events.front() {
+			if retained.received_at.elapsed() <= ttl {
+				break;
+			}
+			events.pop_front();
+		}
+	}
+}
@@ -0,0 +1,72 @@
+use crate::{hooks::HookEvent, models::Value, utils::Result};
+use futures::{channel::mpsc::UnboundedSender, future::BoxFuture};
+use std::sync::{
+	atomic::{AtomicU64, Ordering},
+	Arc,
+};
+
+static NEXT_LISTENER_ID: AtomicU64 = AtomicU64::new(0);
+
+type AsyncListener = dyn Fn(HookEvent) -> BoxFuture<'static, Result<()>> + Send + Sync;
+
+#[derive(Clone)]
+pub enum HookListener {
+	Callback {
+		id: u64,
+		callback: fn(Value),
+	},
+	Async {
+		id: u64,
+		listener: Arc<AsyncListener>,
+	},
+	Subscription {
+		id: u64,
+		sender: UnboundedSender<HookEvent>,
+	},
+	// Keeps a retained hook registered with juno, so that its events are recorded
+	// even while nobody listens to it
+	Retention {
+		id: u64,
+	},
+}
+
+impl HookListener {
+	pub fn next_id() -> u64 {
+		NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed)
+	}
+
+	pub fn get_id(&self) -> u64 {
+		match self {
+			HookListener::Callback { id, .. } => *id,
+			HookListener::Async { id, .. } => *id,
+			HookListener::Subscription { id, .. } => *id,
+			HookListener::Retention { id } => *id,
+		}
+	}
+
+	pub async fn notify(&self, event: HookEvent) -> Result<()> {
+		match self {
+			HookListener::Callback { callback, .. } => callback(event.data),
+			HookListener::Async { listener, .. } => listener(event).await?,
+			HookListener::Subscription { .. } | HookListener::Retention { .. } => {
+				self.queue(&event);
+			}
+		}
+		Ok(())
+	}
+
+	// Subscriptions only queue the event, so they can be notified without waiting on anything.
+	// Retentions have nothing to do, the event is already in the history by then.
+	// Returns whether the listener was notified
+	pub fn queue(&self, event: &HookEvent) -> bool {
+		match self {
+			HookListener::Subscription { sender, .. } => {
+				// If the subscription was just dropped, it's about to be removed anyway
+				sender.unbounded_send(event.clone()).unwrap_or(());
+				true
+			}
+			HookListener::Retention { .. } => true,
+			_ => false,
+		}
+	}
+}
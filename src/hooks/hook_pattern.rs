@@ -0,0 +1,28 @@
+const RESERVED_HOOK_PREFIX: &str = "__juno.";
+
+// Checks if a hook matches the hook (or pattern) a listener was registered with.
+// A `*` in the pattern matches any run of characters, so `orders.*` matches every hook
+// starting with `orders.`, and `*.created` matches every hook ending with `.created`.
+// The hooks juno modules use among themselves (under `__juno.`) only match themselves
+pub fn hook_matches(pattern: &str, hook: &str) -> bool {
+	if !pattern.contains('*') || hook.starts_with(RESERVED_HOOK_PREFIX) {
+		return pattern == hook;
+	}
+
+	let parts: Vec<&str> = pattern.split('*').collect();
+	let first = parts[0];
+	let last = parts[parts.len() - 1];
+	if hook.len() < first.len() + last.len() || !hook.starts_with(first) || !hook.ends_with(last) {
+		return false;
+	}
+
+	// Everything between the first and last part has to show up in order
+	let mut remaining = &hook[first.len()..hook.len() - last.len()];
+	for part in &parts[1..parts.len() - 1] {
+		match remaining.find(part) {
+			Some(index) => remaining = &remaining[index + part.len()..],
+			None => return false,
+		}
+	}
+	true
+}
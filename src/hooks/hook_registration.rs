@@ -0,0 +1,63 @@
+use crate::{
+	juno_module::{ArcHookListenerList, ArcHookRegistrationLock},
+	utils::{RequestSender, Result},
+};
+
+// A hook listener that has been registered with the module.
+// Once the last listener of a hook is unregistered, the hook is unregistered from juno as well
+pub struct HookRegistration {
+	hook: String,
+	listener_id: u64,
+	hook_listeners: ArcHookListenerList,
+	registration_lock: ArcHookRegistrationLock,
+	request_sender: RequestSender,
+}
+
+impl HookRegistration {
+	pub(crate) fn new(
+		hook: String,
+		listener_id: u64,
+		hook_listeners: ArcHookListenerList,
+		registration_lock: ArcHookRegistrationLock,
+		request_sender: RequestSender,
+	) -> Self {
+		HookRegistration {
+			hook,
+			listener_id,
+			hook_listeners,
+			registration_lock,
+			request_sender,
+		}
+	}
+
+	pub fn get_hook(&self) -> &String {
+		&self.hook
+	}
+
+	pub async fn unregister(self) -> Result<()> {
+		// Nobody can register the hook again until juno has unregistered it
+		let _registration_guard = self.registration_lock.lock().await;
+		let mut hook_listeners = self.hook_listeners.lock().await;
+		let is_last_listener = match hook_listeners.get_mut(&self.hook) {
+			Some(listeners) => {
+				listeners.retain(|listener| listener.get_id() != self.listener_id);
+				listeners.is_empty()
+			}
+			None => false,
+		};
+		if !is_last_listener {
+			return Ok(());
+		}
+		hook_listeners.remove(&self.hook);
+		// The data listener locks the requests before the hook listeners.
+		// Let go of the hook listeners before touching the requests, so that they can't deadlock
+		drop(hook_listeners);
+
+		let request = self
+			.request_sender
+			.get_protocol()
+			.unregister_hook(self.hook.clone());
+		self.request_sender.send_request(request).await?;
+		Ok(())
+	}
+}
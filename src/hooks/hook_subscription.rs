@@ -0,0 +1,49 @@
+use crate::hooks::{HookEvent, HookRegistration};
+use async_std::task;
+use futures::{channel::mpsc::UnboundedReceiver, stream::Stream};
+use std::{
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+// A stream of events for a hook. Dropping it unsubscribes from the hook
+pub struct HookSubscription {
+	receiver: UnboundedReceiver<HookEvent>,
+	registration: Option<HookRegistration>,
+}
+
+impl HookSubscription {
+	pub(crate) fn new(
+		receiver: UnboundedReceiver<HookEvent>,
+		registration: HookRegistration,
+	) -> Self {
+		HookSubscription {
+			receiver,
+			registration: Some(registration),
+		}
+	}
+
+	pub fn get_hook(&self) -> &String {
+		self.registration.as_ref().unwrap().get_hook()
+	}
+}
+
+impl Stream for HookSubscription {
+	type Item = HookEvent;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Pin::new(&mut self.receiver).poll_next(cx)
+	}
+}
+
+impl Drop for HookSubscription {
+	fn drop(&mut self) {
+		if let Some(registration) = self.registration.take() {
+			task::spawn(async move {
+				if let Err(err) = registration.unregister().await {
+					println!("Error unsubscribing from hook: {}", err);
+				}
+			});
+		}
+	}
+}
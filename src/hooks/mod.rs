@@ -0,0 +1,20 @@
+mod hook_dispatcher;
+mod hook_event;
+mod hook_history;
+mod hook_listener;
+mod hook_pattern;
+mod hook_registration;
+mod hook_subscription;
+mod scheduled_hook;
+
+pub use hook_dispatcher::HookExecution;
+pub(crate) use hook_dispatcher::{HookDispatcher, HookErrorSink};
+pub use hook_event::HookEvent;
+pub(crate) use hook_history::HookHistory;
+pub use hook_history::{HookReplay, HookRetention};
+pub(crate) use hook_listener::HookListener;
+pub(crate) use hook_pattern::hook_matches;
+pub use hook_registration::HookRegistration;
+pub use hook_subscription::HookSubscription;
+pub(crate) use scheduled_hook::HookSchedule;
+pub use scheduled_hook::ScheduledHook;
@@ -0,0 +1,169 @@
+use crate::{
+	models::Value,
+	utils::{response_of, Error, RequestSender, Result},
+};
+use async_std::task;
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use futures::{
+	channel::oneshot::{channel, Receiver, Sender},
+	future::{self, Either},
+};
+use std::{
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+// When a scheduled hook is triggered
+pub(crate) enum HookSchedule {
+	Every(Duration),
+	Cron(Box<Schedule>),
+	At(Instant),
+}
+
+impl HookSchedule {
+	// Cron expressions are evaluated in UTC, and start with a seconds field:
+	// `sec min hour day-of-month month day-of-week [year]`
+	pub fn cron(expression: &str) -> Result<Self> {
+		match Schedule::from_str(expression) {
+			Ok(schedule) => Ok(HookSchedule::Cron(Box::new(schedule))),
+			Err(err) => Err(Error::Internal(format!(
+				"Invalid cron expression {}: {}",
+				expression, err
+			))),
+		}
+	}
+
+	// When the next trigger is due, if there is one. Intervals are counted from the previous
+	// deadline rather than from when the trigger was sent, so that they don't drift.
+	// Cron slots are counted from the previous slot, so a trigger that fires a little early
+	// can't land on the same slot twice
+	fn next_deadline(&self, previous: Option<Deadline>) -> Option<Deadline> {
+		match self {
+			HookSchedule::Every(every) => Some(Deadline {
+				instant: previous.map_or_else(Instant::now, |previous| previous.instant) + *every,
+				slot: None,
+			}),
+			HookSchedule::Cron(schedule) => {
+				let next = match previous.and_then(|previous| previous.slot) {
+					Some(slot) => schedule.after(&slot).next()?,
+					None => schedule.upcoming(Utc).next()?,
+				};
+				Some(Deadline {
+					instant: Instant::now() + (next - Utc::now()).to_std().unwrap_or_default(),
+					slot: Some(next),
+				})
+			}
+			HookSchedule::At(_) if previous.is_some() => None,
+			HookSchedule::At(instant) => Some(Deadline {
+				instant: *instant,
+				slot: None,
+			}),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+struct Deadline {
+	instant: Instant,
+	// The time the cron expression matched, for cron schedules
+	slot: Option<DateTime<Utc>>,
+}
+
+// A hook that is triggered by the module on a schedule, until it's cancelled.
+// Dropping the handle leaves the schedule running
+pub struct ScheduledHook {
+	hook: String,
+	cancel_sender: Sender<()>,
+}
+
+impl ScheduledHook {
+	pub(crate) fn spawn(
+		hook: String,
+		data: Value,
+		schedule: HookSchedule,
+		request_sender: RequestSender,
+	) -> Self {
+		let (cancel_sender, cancel_receiver) = channel::<()>();
+		task::spawn(run_schedule(
+			hook.clone(),
+			data,
+			schedule,
+			request_sender,
+			cancel_receiver,
+		));
+		ScheduledHook {
+			hook,
+			cancel_sender,
+		}
+	}
+
+	pub fn get_hook(&self) -> &String {
+		&self.hook
+	}
+
+	// Stops the schedule. A trigger that has already been sent isn't affected
+	pub fn cancel(self) {
+		self.cancel_sender.send(()).unwrap_or(());
+	}
+}
+
+async fn run_schedule(
+	hook: String,
+	data: Value,
+	schedule: HookSchedule,
+	request_sender: RequestSender,
+	cancel_receiver: Receiver<()>,
+) {
+	let mut cancel_receiver = Some(cancel_receiver);
+	let mut previous = None;
+	while let Some(deadline) = schedule.next_deadline(previous) {
+		let delay = deadline.instant.saturating_duration_since(Instant::now());
+		if wait_unless_cancelled(delay, &mut cancel_receiver).await {
+			return;
+		}
+		let request = request_sender
+			.get_protocol()
+			.trigger_hook(hook.clone(), data.clone());
+		// The schedule doesn't wait for juno, so a slow response can't hold up the next trigger
+		let response = match request_sender.start_request(request).await {
+			Ok(response) => response,
+			// The module was closed, so there's nothing left to trigger the hook on
+			Err(err) => {
+				println!("Stopping scheduled hook {}: {}", hook, err);
+				return;
+			}
+		};
+		let hook = hook.clone();
+		task::spawn(async move {
+			if let Err(err) = response_of(response).await {
+				println!("Error triggering scheduled hook {}: {}", hook, err);
+			}
+		});
+		previous = Some(deadline);
+	}
+}
+
+// Returns true if the schedule was cancelled before the delay was up
+async fn wait_unless_cancelled(
+	delay: Duration,
+	cancel_receiver: &mut Option<Receiver<()>>,
+) -> bool {
+	let receiver = match cancel_receiver.as_mut() {
+		Some(receiver) => receiver,
+		None => {
+			task::sleep(delay).await;
+			return false;
+		}
+	};
+	match future::select(Box::pin(task::sleep(delay)), receiver).await {
+		Either::Left(_) => false,
+		Either::Right((Ok(()), _)) => true,
+		// The handle was dropped without cancelling, so the schedule can't be cancelled anymore
+		Either::Right((Err(_), sleep)) => {
+			*cancel_receiver = None;
+			sleep.await;
+			false
+		}
+	}
+}
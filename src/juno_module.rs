@@ -7,9 +7,26 @@ pub use serde_json::json;
 
 use crate::{
 	connection::{BaseConnection, Buffer, InetSocketConnection},
-	models::{BaseMessage, Value},
+	functions::{
+		finish_running_call, send_function_response, CallGuard, CircuitBreaker,
+		CircuitBreakerPolicy, CircuitState, CircuitStateChange, DelayedCall, FunctionCaller,
+		FunctionContext, FunctionHandler, FunctionProgress, FunctionStream, IncomingCall,
+		JunoService, ModuleAvailability, ModuleDirectory, ModuleStatus, PendingCall,
+		PendingRequest, Responder, RetryPolicy, ServiceFunctions, DISCOVERY_HOOK,
+	},
+	hooks::{
+		HookDispatcher, HookErrorSink, HookEvent, HookExecution, HookHistory, HookListener,
+		HookRegistration, HookReplay, HookRetention, HookSchedule, HookSubscription, ScheduledHook,
+	},
+	juno_module_builder::CIRCUIT_BREAKER_WITHOUT_TIMEOUT,
+	juno_module_config::ModuleConfig,
+	models::{parse_version, BaseMessage, Dependency, ErrorSource, Value},
 	protocol::BaseProtocol,
-	utils::{self, Error, Result},
+	utils::{
+		self, connection_closed, errors, expect_response, request_types, response_of, Error,
+		RequestSender, Result,
+	},
+	JunoModuleBuilder,
 };
 
 #[cfg(target_family = "unix")]
@@ -20,31 +37,95 @@ use async_std::{
 	sync::{Arc, Mutex},
 	task,
 };
-use futures::channel::{
-	mpsc::{UnboundedReceiver, UnboundedSender},
-	oneshot::{channel, Sender},
+use futures::{
+	channel::{
+		mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+		oneshot::Sender,
+	},
+	future,
+	stream::Stream,
 };
 use futures_util::sink::SinkExt;
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
 	collections::HashMap,
+	env,
 	net::{AddrParseError, SocketAddr},
+	path::Path,
+	time::{Duration, Instant},
 };
 
-type ArcRequestList = Arc<Mutex<HashMap<String, Sender<Result<Value>>>>>;
-type ArcFunctionList = Arc<Mutex<HashMap<String, fn(HashMap<String, Value>) -> Value>>>;
-type ArcHookListenerList = Arc<Mutex<HashMap<String, Vec<fn(Value)>>>>;
+// How often wait_for_module checks whether the module has shown up
+const MODULE_PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+pub(crate) type ArcRequestList = Arc<Mutex<HashMap<String, PendingRequest>>>;
+type ArcFunctionList = Arc<Mutex<HashMap<String, FunctionHandler>>>;
+pub(crate) type ArcRunningCallList = Arc<Mutex<HashMap<String, Sender<()>>>>;
+pub(crate) type ArcHookListenerList = Arc<Mutex<HashMap<String, Vec<HookListener>>>>;
+pub(crate) type ArcHookHistory = Arc<Mutex<HookHistory>>;
+// Held from the change to a hook's listeners until juno has answered the (un)registration that
+// came with it, so that juno sees registrations in the same order as the listeners changed.
+// The data listener never takes it, so it can be held while waiting for juno
+pub(crate) type ArcHookRegistrationLock = Arc<Mutex<()>>;
 
 pub struct JunoModule {
 	protocol: BaseProtocol,
 	connection: Box<dyn BaseConnection + Send + Sync>,
 	requests: ArcRequestList,
 	functions: ArcFunctionList,
+	running_calls: ArcRunningCallList,
 	hook_listeners: ArcHookListenerList,
+	hook_history: ArcHookHistory,
+	hook_registration_lock: ArcHookRegistrationLock,
+	retained_hooks: HashMap<String, HookRegistration>,
+	hook_execution: HookExecution,
+	hook_error_sink: HookErrorSink,
+	call_timeout: Option<Duration>,
+	max_running_calls: Option<usize>,
+	retry_policy: Option<RetryPolicy>,
+	circuit_breaker: Option<CircuitBreaker>,
 	message_buffer: Buffer,
+	connected: bool,
 	registered: bool,
+	dependencies: Vec<String>,
+	module_directory: ModuleDirectory,
 }
 
 impl JunoModule {
+	// Configures the module step by step, and connects and registers it once it's built
+	pub fn builder() -> JunoModuleBuilder {
+		JunoModuleBuilder::new()
+	}
+
+	// A builder configured from JUNO_* environment variables, on top of the config file that
+	// JUNO_CONFIG points to, if any. From highest to lowest, the precedence is:
+	// whatever is set on the builder afterwards, the environment, the config file, and then
+	// the defaults (juno is reached at DEFAULT_ADDRESS)
+	pub fn from_env() -> Result<JunoModuleBuilder> {
+		let config = match env::var("JUNO_CONFIG") {
+			Ok(path) => ModuleConfig::from_file(Path::new(&path))?,
+			Err(_) => ModuleConfig::default(),
+		};
+		config.merge(ModuleConfig::from_env()?).into_builder()
+	}
+
+	// Same as from_env, but with the given config file. It can be either toml or json
+	pub fn from_config<P: AsRef<Path>>(path: P) -> Result<JunoModuleBuilder> {
+		ModuleConfig::from_file(path.as_ref())?
+			.merge(ModuleConfig::from_env()?)
+			.into_builder()
+	}
+
+	// Same as from_config, with the JUNO_* variables taken from the map instead of the environment
+	pub fn from_config_with_vars<P: AsRef<Path>>(
+		path: P,
+		vars: &HashMap<String, String>,
+	) -> Result<JunoModuleBuilder> {
+		ModuleConfig::from_file(path.as_ref())?
+			.merge(ModuleConfig::from_vars(vars)?)
+			.into_builder()
+	}
+
 	pub fn default(connection_path: &str) -> Self {
 		let is_ip: std::result::Result<SocketAddr, AddrParseError> =
 			connection_path.to_string().parse();
@@ -62,27 +143,17 @@ impl JunoModule {
 
 	#[cfg(target_family = "unix")]
 	pub fn from_unix_socket(socket_path: &str) -> Self {
-		JunoModule {
-			protocol: BaseProtocol::default(),
-			connection: Box::new(UnixSocketConnection::new(socket_path.to_string())),
-			requests: Arc::new(Mutex::new(HashMap::new())),
-			functions: Arc::new(Mutex::new(HashMap::new())),
-			hook_listeners: Arc::new(Mutex::new(HashMap::new())),
-			message_buffer: vec![],
-			registered: false,
-		}
+		Self::new(
+			BaseProtocol::default(),
+			Box::new(UnixSocketConnection::new(socket_path.to_string())),
+		)
 	}
 
 	pub fn from_inet_socket(host: &str, port: u16) -> Self {
-		JunoModule {
-			protocol: BaseProtocol::default(),
-			connection: Box::new(InetSocketConnection::new(format!("{}:{}", host, port))),
-			requests: Arc::new(Mutex::new(HashMap::new())),
-			functions: Arc::new(Mutex::new(HashMap::new())),
-			hook_listeners: Arc::new(Mutex::new(HashMap::new())),
-			message_buffer: vec![],
-			registered: false,
-		}
+		Self::new(
+			BaseProtocol::default(),
+			Box::new(InetSocketConnection::new(format!("{}:{}", host, port))),
+		)
 	}
 
 	pub fn new(protocol: BaseProtocol, connection: Box<dyn BaseConnection + Send + Sync>) -> Self {
@@ -91,12 +162,76 @@ impl JunoModule {
 			connection,
 			requests: Arc::new(Mutex::new(HashMap::new())),
 			functions: Arc::new(Mutex::new(HashMap::new())),
+			running_calls: Arc::new(Mutex::new(HashMap::new())),
 			hook_listeners: Arc::new(Mutex::new(HashMap::new())),
+			hook_history: Arc::new(Mutex::new(HookHistory::default())),
+			hook_registration_lock: Arc::new(Mutex::new(())),
+			retained_hooks: HashMap::new(),
+			hook_execution: HookExecution::default(),
+			hook_error_sink: Arc::new(|hook, err| {
+				println!("Listener of hook {} failed: {}", hook, err)
+			}),
+			call_timeout: None,
+			max_running_calls: None,
+			retry_policy: None,
+			circuit_breaker: None,
 			message_buffer: vec![],
+			connected: false,
 			registered: false,
+			dependencies: vec![],
+			module_directory: ModuleDirectory::new(),
 		}
 	}
 
+	// Whether the listeners of a hook run one after the other (the default) or all at once.
+	// Fails once the module is initialized
+	pub fn set_hook_execution(&mut self, execution: HookExecution) -> Result<()> {
+		self.ensure_not_connected("The hook execution")?;
+		self.hook_execution = execution;
+		Ok(())
+	}
+
+	// Puts a circuit breaker in front of every call_function of this module,
+	// so that calls to a module that keeps failing fail fast instead of piling up.
+	// Fails unless the module has a call timeout
+	pub fn set_circuit_breaker(&mut self, policy: CircuitBreakerPolicy) -> Result<()> {
+		if self.call_timeout.is_none() {
+			return Err(Error::Configuration(String::from(
+				CIRCUIT_BREAKER_WITHOUT_TIMEOUT,
+			)));
+		}
+		self.circuit_breaker = Some(CircuitBreaker::new(policy));
+		Ok(())
+	}
+
+	// The state of the module's circuit, if there's a circuit breaker
+	pub async fn circuit_state(&self, module_id: &str) -> Option<CircuitState> {
+		match &self.circuit_breaker {
+			Some(circuit_breaker) => Some(circuit_breaker.get_state(module_id).await),
+			None => None,
+		}
+	}
+
+	// Receives every change of state of every module's circuit, if there's a circuit breaker
+	pub async fn circuit_state_changes(&self) -> Option<UnboundedReceiver<CircuitStateChange>> {
+		match &self.circuit_breaker {
+			Some(circuit_breaker) => Some(circuit_breaker.observe().await),
+			None => None,
+		}
+	}
+
+	// Receives the errors of every hook listener that fails or panics, as well as the payloads
+	// that typed hook listeners couldn't deserialize. By default, they're printed.
+	// Fails once the module is initialized
+	pub fn set_hook_error_sink<F>(&mut self, error_sink: F) -> Result<()>
+	where
+		F: Fn(&str, Error) + Send + Sync + 'static,
+	{
+		self.ensure_not_connected("The hook error sink")?;
+		self.hook_error_sink = Arc::new(error_sink);
+		Ok(())
+	}
+
 	pub async fn initialize(
 		&mut self,
 		module_id: &str,
@@ -104,14 +239,55 @@ impl JunoModule {
 		dependencies: HashMap<String, String>,
 	) -> Result<()> {
 		self.setup_connections().await?;
+		self.register(module_id, version, dependencies).await
+	}
+
+	// Every call this module makes fails with Error::Timeout if it isn't responded to in time
+	pub fn set_call_timeout(&mut self, timeout: Duration) {
+		self.call_timeout = Some(timeout);
+	}
+
+	// Calls that would run on their own task are rejected once this many are running already.
+	// Fails once the module is initialized
+	pub fn set_max_running_calls(&mut self, max_running_calls: usize) -> Result<()> {
+		self.ensure_not_connected("The maximum of running calls")?;
+		self.max_running_calls = Some(max_running_calls);
+		Ok(())
+	}
+
+	// Every call_function of this module is retried according to the policy
+	pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+		self.retry_policy = Some(retry_policy);
+	}
+
+	pub(crate) async fn register(
+		&mut self,
+		module_id: &str,
+		version: &str,
+		dependencies: HashMap<String, String>,
+	) -> Result<()> {
+		// Catch malformed versions before juno gets to reject them, or worse, accept them
+		parse_version(module_id, version)?;
+		let requirements = dependencies
+			.iter()
+			.map(|(dependency, requirement)| Dependency::new(dependency, requirement))
+			.collect::<Result<Vec<_>>>()?;
 
 		let request =
 			self.protocol
 				.initialize(String::from(module_id), String::from(version), dependencies);
-		self.send_request(request).await?;
+		if let Err(err) = self.send_request(request).await {
+			return Err(registration_error(module_id, version, &requirements, err));
+		}
 
+		let mut dependencies = requirements
+			.iter()
+			.map(|dependency| dependency.get_module_id().to_string())
+			.collect::<Vec<_>>();
+		dependencies.sort();
+		self.dependencies = dependencies;
 		self.registered = true;
-		Ok(())
+		self.discover_modules(module_id).await
 	}
 
 	pub async fn declare_function(
@@ -119,55 +295,613 @@ impl JunoModule {
 		fn_name: &str,
 		function: fn(HashMap<String, Value>) -> Value,
 	) -> Result<()> {
-		let fn_name = fn_name.to_string();
-		self.functions
-			.lock()
+		self.declare_function_handler(fn_name, FunctionHandler::Infallible(function))
 			.await
-			.insert(fn_name.clone(), function);
+	}
 
-		let request = self.protocol.declare_function(fn_name);
-		self.send_request(request).await?;
+	// Same as declare_async_function, but the function answers through the Responder it's given,
+	// which can be stored or moved elsewhere until the answer is ready.
+	// The call fails with Error::Timeout if it isn't answered in time
+	pub async fn declare_deferred_function<F>(
+		&mut self,
+		fn_name: &str,
+		timeout: Duration,
+		function: F,
+	) -> Result<()>
+	where
+		F: Fn(FunctionContext, HashMap<String, Value>, Responder) + Send + Sync + 'static,
+	{
+		self.declare_function_handler(
+			fn_name,
+			FunctionHandler::from_deferred(Some(timeout), function),
+		)
+		.await
+	}
+
+	// Declares every function of the service under its prefix.
+	// Stops at the first function that can't be declared
+	pub async fn register_service<S: JunoService>(&mut self, service: Arc<S>) -> Result<()> {
+		let mut functions = ServiceFunctions::new(service);
+		S::declare_functions(&mut functions);
+		for (fn_name, handler) in functions.into_handlers() {
+			self.declare_function_handler(&fn_name, handler).await?;
+		}
 		Ok(())
 	}
 
-	pub async fn call_function(
+	// Declares the function, but instead of running a handler, every call to it is handed over
+	// through the returned stream. Each call has to be responded to (or failed) by its reader
+	pub async fn incoming_calls(
 		&mut self,
 		fn_name: &str,
+	) -> Result<UnboundedReceiver<IncomingCall>> {
+		let (sender, receiver) = unbounded::<IncomingCall>();
+		self.declare_function_handler(fn_name, FunctionHandler::Incoming(sender))
+			.await?;
+		Ok(receiver)
+	}
+
+	// Same as declare_function, but the function can fail with an error code,
+	// which is sent back to the caller along with an optional message and details
+	pub async fn declare_fallible_function(
+		&mut self,
+		fn_name: &str,
+		function: fn(HashMap<String, Value>) -> Result<Value>,
+	) -> Result<()> {
+		self.declare_function_handler(fn_name, FunctionHandler::Fallible(function))
+			.await
+	}
+
+	// Same as declare_function, but the function runs on its own task.
+	// The context tells the function if the caller cancelled the call
+	pub async fn declare_async_function<F, Fut>(&mut self, fn_name: &str, function: F) -> Result<()>
+	where
+		F: Fn(FunctionContext, HashMap<String, Value>) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<Value>> + Send + 'static,
+	{
+		self.declare_function_handler(fn_name, FunctionHandler::from_async(function))
+			.await
+	}
+
+	// Same as declare_async_function, but the function responds with a stream of values.
+	// Each value is sent to the caller as soon as the stream yields it. There's no backpressure:
+	// the stream is pulled as fast as it yields, and chunks the socket hasn't caught up with yet
+	// are buffered in memory. A producer that outpaces the connection has to pace itself
+	pub async fn declare_stream_function<F, S>(&mut self, fn_name: &str, function: F) -> Result<()>
+	where
+		F: Fn(FunctionContext, HashMap<String, Value>) -> S + Send + Sync + 'static,
+		S: Stream<Item = Value> + Send + 'static,
+	{
+		self.declare_function_handler(fn_name, FunctionHandler::from_stream(function))
+			.await
+	}
+
+	pub async fn call_function(
+		&self,
+		fn_name: &str,
 		args: HashMap<String, Value>,
 	) -> Result<Value> {
-		let fn_name = fn_name.to_string();
 		self.ensure_registered()?;
-		let request = self.protocol.call_function(fn_name, args);
-		self.send_request(request).await
+		self.function_caller().call(fn_name, args).await
 	}
 
-	pub async fn register_hook(&mut self, hook: &str, callback: fn(Value)) -> Result<()> {
-		let hook = hook.to_string();
+	// Same as call_function, but retried according to the given policy instead of the
+	// module's. Every attempt is a new call, with a request id of its own
+	pub async fn call_function_with_retry(
+		&self,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+		retry_policy: &RetryPolicy,
+	) -> Result<Value> {
 		self.ensure_registered()?;
-		let mut hook_listeners = self.hook_listeners.lock().await;
-		if hook_listeners.contains_key(&hook) {
-			hook_listeners.get_mut(&hook).unwrap().push(callback);
-		} else {
-			hook_listeners.insert(hook.clone(), vec![callback]);
+		self.function_caller()
+			.call_with_retry(fn_name, args, retry_policy)
+			.await
+	}
+
+	// Calls the function once the delay is up, like call_function would. The call can be
+	// cancelled until then, and the returned handle resolves to its result afterwards
+	pub fn call_function_after(
+		&self,
+		delay: Duration,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+	) -> Result<DelayedCall> {
+		self.ensure_registered()?;
+		Ok(DelayedCall::spawn(
+			delay,
+			fn_name.to_string(),
+			args,
+			self.function_caller(),
+		))
+	}
+
+	// Same as call_function_after, but the function is called at the given instant
+	pub fn call_function_at(
+		&self,
+		instant: Instant,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+	) -> Result<DelayedCall> {
+		let delay = instant.saturating_duration_since(Instant::now());
+		self.call_function_after(delay, fn_name, args)
+	}
+
+	// Sends the function call, and returns the pending call without waiting for the response.
+	// The call can be cancelled by calling cancel() on it, or by just dropping it
+	pub async fn start_function_call(
+		&self,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+	) -> Result<PendingCall> {
+		self.ensure_registered()?;
+		self.function_caller().start(fn_name, args).await
+	}
+
+	// Calls every function at once, and returns their results in the same order.
+	// A call that fails doesn't affect the others
+	pub async fn call_many(
+		&self,
+		calls: Vec<(&str, HashMap<String, Value>)>,
+	) -> Vec<Result<Value>> {
+		future::join_all(
+			calls
+				.into_iter()
+				.map(|(fn_name, args)| self.call_function(fn_name, args)),
+		)
+		.await
+	}
+
+	// Same as call_many, but fails with the first error.
+	// The calls that are still running by then are cancelled
+	pub async fn try_call_many(
+		&self,
+		calls: Vec<(&str, HashMap<String, Value>)>,
+	) -> Result<Vec<Value>> {
+		future::try_join_all(
+			calls
+				.into_iter()
+				.map(|(fn_name, args)| self.call_function(fn_name, args)),
+		)
+		.await
+	}
+
+	// Calls `{module_id}.{function_suffix}` on every other module connected to juno, and collects
+	// the results that arrive before the timeout, ordered by module id.
+	// The modules called are the ones this one depends on, and the ones it heard of through the
+	// discovery hook. Modules that turn out not to be registered or not to declare the function
+	// are left out, and so are the ones whose circuit is open and the calls that are still
+	// running at the timeout, which are cancelled.
+	// Each call goes through the call timeout, the circuit breaker and the retry policy, like
+	// call_function. Fails if there are no modules to call
+	pub async fn broadcast_call(
+		&self,
+		function_suffix: &str,
+		args: HashMap<String, Value>,
+		timeout: Duration,
+	) -> Result<Vec<(String, Result<Value>)>> {
+		self.ensure_registered()?;
+		let mut modules = self.module_directory.modules().await;
+		modules.extend(self.dependencies.iter().cloned());
+		modules.sort();
+		modules.dedup();
+		if modules.is_empty() {
+			return Err(Error::Internal(format!(
+				"No modules to broadcast {} to",
+				function_suffix
+			)));
 		}
-		drop(hook_listeners);
 
-		let request = self.protocol.register_hook(hook);
+		let function_caller = self.function_caller();
+		let calls = modules.into_iter().map(|module_id| {
+			let fn_name = format!("{}.{}", module_id, function_suffix);
+			let args = args.clone();
+			let function_caller = &function_caller;
+			async move {
+				let call = function_caller.call(&fn_name, args);
+				match async_std::future::timeout(timeout, call).await {
+					Ok(Err(Error::FromJuno(errors::UNKNOWN_MODULE)))
+					| Ok(Err(Error::FromJuno(errors::UNKNOWN_FUNCTION)))
+					| Ok(Err(Error::FromModule {
+						code: errors::UNKNOWN_FUNCTION,
+						..
+					}))
+					| Ok(Err(Error::CircuitOpen(_)))
+					| Ok(Err(Error::Timeout))
+					| Err(_) => None,
+					Ok(result) => Some((module_id, result)),
+				}
+			}
+		});
+		Ok(future::join_all(calls)
+			.await
+			.into_iter()
+			.flatten()
+			.collect())
+	}
+
+	// Follows whether the module is registered with juno, probing it every interval
+	pub fn module_availability(
+		&self,
+		module_id: &str,
+		interval: Duration,
+	) -> Result<ModuleAvailability> {
+		self.ensure_registered()?;
+		Ok(ModuleAvailability::spawn(
+			module_id.to_string(),
+			interval,
+			self.request_sender(),
+		))
+	}
+
+	// Resolves once the module is registered with juno.
+	// Fails with Error::Timeout if it doesn't show up in time
+	pub async fn wait_for_module(&self, module_id: &str, timeout: Duration) -> Result<()> {
+		let mut availability = self.module_availability(module_id, MODULE_PROBE_INTERVAL)?;
+		let available = async {
+			while let Some(status) = availability.next().await {
+				if status == ModuleStatus::Available {
+					return Ok(());
+				}
+			}
+			Err(Error::Internal(String::from(
+				"Module availability stopped before the module became available",
+			)))
+		};
+		match async_std::future::timeout(timeout, available).await {
+			Ok(result) => result,
+			Err(_) => Err(Error::Timeout),
+		}
+	}
+
+	// Calls a function and returns its response as a stream, chunk by chunk.
+	// Functions that respond with a single value give a stream of one item.
+	// Chunks that arrive faster than the stream is read are buffered in memory until they're read.
+	// Unlike call_function, the stream isn't timed out, retried, or counted by the circuit
+	// breaker, since a stream that has already yielded chunks can't be sent again
+	pub async fn call_function_stream(
+		&self,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+	) -> Result<FunctionStream> {
+		let (sender, receiver) = unbounded::<Result<Value>>();
+		let guard = self
+			.send_function_call(fn_name, args, PendingRequest::Stream(sender))
+			.await?;
+		Ok(FunctionStream::new(guard, receiver))
+	}
+
+	// The hook can also be a pattern like `orders.*` or `*.created`, which is registered with juno
+	// as is, and matched against every hook the module receives.
+	// The returned registration can be used to unregister this callback later on
+	pub async fn register_hook(
+		&mut self,
+		hook: &str,
+		callback: fn(Value),
+	) -> Result<HookRegistration> {
+		let listener = HookListener::Callback {
+			id: HookListener::next_id(),
+			callback,
+		};
+		self.add_hook_listener(hook, listener, None).await
+	}
+
+	// Same as register_hook, but the listener is an async closure, which can hold its own state.
+	// A listener that fails or panics is logged and doesn't affect the other listeners
+	pub async fn register_async_hook<F, Fut>(
+		&mut self,
+		hook: &str,
+		listener: F,
+	) -> Result<HookRegistration>
+	where
+		F: Fn(HookEvent) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<()>> + Send + 'static,
+	{
+		let listener = HookListener::Async {
+			id: HookListener::next_id(),
+			listener: Arc::new(move |event| Box::pin(listener(event))),
+		};
+		self.add_hook_listener(hook, listener, None).await
+	}
+
+	// Same as register_hook, but the payload is deserialized into T before the listener is called.
+	// Payloads that can't be deserialized are reported to the hook error sink
+	pub async fn register_typed_hook<T, F>(
+		&mut self,
+		hook: &str,
+		listener: F,
+	) -> Result<HookRegistration>
+	where
+		T: DeserializeOwned + 'static,
+		F: Fn(T) + Send + Sync + 'static,
+	{
+		self.register_async_hook(hook, move |event| {
+			let result = event.data.deserialize::<T>().map(&listener);
+			future::ready(result)
+		})
+		.await
+	}
+
+	// Same as register_hook, but the hook's events are delivered as a stream.
+	// Each event has the name of the hook that was triggered, even when subscribing to a pattern.
+	// Dropping the stream unsubscribes from the hook
+	pub async fn subscribe(&mut self, hook: &str) -> Result<HookSubscription> {
+		let (sender, receiver) = unbounded::<HookEvent>();
+		let listener = HookListener::Subscription {
+			id: HookListener::next_id(),
+			sender,
+		};
+		let registration = self.add_hook_listener(hook, listener, None).await?;
+		Ok(HookSubscription::new(receiver, registration))
+	}
+
+	// Same as subscribe, but the stream starts with the events this module has retained for the
+	// hook (see retain_hook), so that a late subscriber doesn't miss the ones already triggered
+	pub async fn subscribe_with_replay(
+		&mut self,
+		hook: &str,
+		replay: HookReplay,
+	) -> Result<HookSubscription> {
+		let (sender, receiver) = unbounded::<HookEvent>();
+		let listener = HookListener::Subscription {
+			id: HookListener::next_id(),
+			sender,
+		};
+		let registration = self.add_hook_listener(hook, listener, Some(replay)).await?;
+		Ok(HookSubscription::new(receiver, registration))
+	}
+
+	// Keeps the recent events of a hook (or of every hook matching a pattern) in memory,
+	// to be replayed to subscribers. The hook stays registered with juno until it's released,
+	// so its events are kept even while nobody listens to it.
+	// Retaining a hook again only changes its retention
+	pub async fn retain_hook(&mut self, hook: &str, retention: HookRetention) -> Result<()> {
+		self.ensure_registered()?;
+		self.hook_history
+			.lock()
+			.await
+			.retain(hook.to_string(), retention);
+		if self.retained_hooks.contains_key(hook) {
+			return Ok(());
+		}
+
+		let listener = HookListener::Retention {
+			id: HookListener::next_id(),
+		};
+		match self.add_hook_listener(hook, listener, None).await {
+			Ok(registration) => {
+				self.retained_hooks.insert(hook.to_string(), registration);
+				Ok(())
+			}
+			Err(err) => {
+				self.hook_history.lock().await.release(hook);
+				Err(err)
+			}
+		}
+	}
+
+	// Stops retaining a hook and drops the events kept for it.
+	// The hook is unregistered from juno if nobody else listens to it
+	pub async fn release_hook(&mut self, hook: &str) -> Result<()> {
+		self.hook_history.lock().await.release(hook);
+		match self.retained_hooks.remove(hook) {
+			Some(registration) => registration.unregister().await,
+			None => Ok(()),
+		}
+	}
+
+	// Swaps the implementation of an already declared function, without declaring it to juno again.
+	// Calls that are already running finish on the old implementation
+	pub async fn replace_function(
+		&mut self,
+		fn_name: &str,
+		function: FunctionHandler,
+	) -> Result<()> {
+		self.ensure_registered()?;
+		let mut functions = self.functions.lock().await;
+		match functions.get_mut(fn_name) {
+			Some(existing_function) => {
+				*existing_function = function;
+				Ok(())
+			}
+			None => Err(Error::Internal(format!(
+				"Function {} was never declared",
+				fn_name
+			))),
+		}
+	}
+
+	// Stops handling calls to the function, and lets juno know that it doesn't exist anymore.
+	// Calls that come in after this are rejected with UNKNOWN_FUNCTION
+	pub async fn undeclare_function(&mut self, fn_name: &str) -> Result<()> {
+		self.ensure_registered()?;
+		if self.functions.lock().await.remove(fn_name).is_none() {
+			return Err(Error::Internal(format!(
+				"Function {} was never declared",
+				fn_name
+			)));
+		}
+
+		let request = self.protocol.undeclare_function(fn_name.to_string());
 		self.send_request(request).await?;
 		Ok(())
 	}
 
 	pub async fn trigger_hook(&mut self, hook: &str) -> Result<()> {
+		self.trigger_hook_with_data(hook, Value::Null).await
+	}
+
+	pub async fn trigger_hook_with_data(&mut self, hook: &str, data: Value) -> Result<()> {
 		let hook = hook.to_string();
-		let request = self.protocol.trigger_hook(hook);
+		let request = self.protocol.trigger_hook(hook, data);
 		self.send_request(request).await?;
 		Ok(())
 	}
 
+	// Triggers the hook every time the interval elapses, starting one interval from now
+	pub fn schedule_hook(
+		&mut self,
+		hook: &str,
+		every: Duration,
+		data: Value,
+	) -> Result<ScheduledHook> {
+		self.schedule_hook_with(hook, HookSchedule::Every(every), data)
+	}
+
+	// Triggers the hook at every time the cron expression matches, in UTC.
+	// The expression starts with seconds: `sec min hour day-of-month month day-of-week [year]`
+	pub fn schedule_hook_cron(
+		&mut self,
+		hook: &str,
+		expression: &str,
+		data: Value,
+	) -> Result<ScheduledHook> {
+		self.schedule_hook_with(hook, HookSchedule::cron(expression)?, data)
+	}
+
+	// Triggers the hook once, at the given instant
+	pub fn trigger_hook_at(
+		&mut self,
+		hook: &str,
+		instant: Instant,
+		data: Value,
+	) -> Result<ScheduledHook> {
+		self.schedule_hook_with(hook, HookSchedule::At(instant), data)
+	}
+
+	// Same as trigger_hook_with_data, but the payload is serialized with serde
+	pub async fn trigger_typed_hook<T>(&mut self, hook: &str, data: &T) -> Result<()>
+	where
+		T: Serialize + ?Sized,
+	{
+		let data = Value::serialize(data)?;
+		self.trigger_hook_with_data(hook, data).await
+	}
+
 	pub async fn close(&mut self) {
 		self.connection.close_connection().await;
 	}
 
+	async fn declare_function_handler(
+		&mut self,
+		fn_name: &str,
+		function: FunctionHandler,
+	) -> Result<()> {
+		let fn_name = fn_name.to_string();
+		let mut functions = self.functions.lock().await;
+		if functions.contains_key(&fn_name) {
+			return Err(Error::Internal(format!(
+				"Function {} is already declared. Use replace_function to change its implementation",
+				fn_name
+			)));
+		}
+		functions.insert(fn_name.clone(), function);
+		drop(functions);
+
+		let request = self.protocol.declare_function(fn_name.clone());
+		if let Err(err) = self.send_request(request).await {
+			// Juno doesn't know of the function, so it can be declared again
+			self.functions.lock().await.remove(&fn_name);
+			return Err(err);
+		}
+		Ok(())
+	}
+
+	async fn add_hook_listener(
+		&mut self,
+		hook: &str,
+		listener: HookListener,
+		replay: Option<HookReplay>,
+	) -> Result<HookRegistration> {
+		let hook = hook.to_string();
+		self.ensure_registered()?;
+		let listener_id = listener.get_id();
+		let registration_lock = self.hook_registration_lock.clone();
+		let registration_guard = registration_lock.lock().await;
+		let mut hook_listeners = self.hook_listeners.lock().await;
+		// Replayed while the listeners are locked, so no hook can be triggered in between
+		if let (Some(replay), HookListener::Subscription { sender, .. }) = (replay, &listener) {
+			for event in self.hook_history.lock().await.replay(&hook, replay) {
+				sender.unbounded_send(event).unwrap_or(());
+			}
+		}
+		let listeners = hook_listeners.entry(hook.clone()).or_insert_with(Vec::new);
+		listeners.push(listener);
+		// Only the first listener of a hook needs to register it with juno
+		let is_first_listener = listeners.len() == 1;
+		drop(hook_listeners);
+
+		if is_first_listener {
+			let request = self.protocol.register_hook(hook.clone());
+			self.send_request(request).await?;
+		}
+		drop(registration_guard);
+		Ok(HookRegistration::new(
+			hook,
+			listener_id,
+			self.hook_listeners.clone(),
+			self.hook_registration_lock.clone(),
+			self.request_sender(),
+		))
+	}
+
+	// Announces the module on the discovery hook, and keeps track of the modules that announce
+	// themselves there, so that broadcast_call can reach them
+	async fn discover_modules(&mut self, module_id: &str) -> Result<()> {
+		let listener = self.module_directory.listener(self.request_sender());
+		self.add_hook_listener(DISCOVERY_HOOK, listener, None)
+			.await?;
+		self.trigger_hook_with_data(
+			DISCOVERY_HOOK,
+			ModuleDirectory::announcement(module_id, false),
+		)
+		.await
+	}
+
+	fn schedule_hook_with(
+		&mut self,
+		hook: &str,
+		schedule: HookSchedule,
+		data: Value,
+	) -> Result<ScheduledHook> {
+		self.ensure_registered()?;
+		Ok(ScheduledHook::spawn(
+			hook.to_string(),
+			data,
+			schedule,
+			self.request_sender(),
+		))
+	}
+
+	fn request_sender(&self) -> RequestSender {
+		RequestSender::new(
+			self.requests.clone(),
+			self.connection.clone_write_sender(),
+			Arc::new(BaseProtocol::from(&self.protocol)),
+		)
+	}
+
+	fn function_caller(&self) -> FunctionCaller {
+		FunctionCaller::new(
+			self.request_sender(),
+			self.call_timeout,
+			self.retry_policy.clone(),
+			self.circuit_breaker.clone(),
+		)
+	}
+
+	async fn send_function_call(
+		&self,
+		fn_name: &str,
+		args: HashMap<String, Value>,
+		pending_request: PendingRequest,
+	) -> Result<CallGuard> {
+		self.ensure_registered()?;
+		self.request_sender()
+			.send_function_call(fn_name.to_string(), args, pending_request)
+			.await
+	}
+
 	fn ensure_registered(&self) -> Result<()> {
 		if !self.registered {
 			return Err(Error::Internal(String::from(
@@ -177,8 +911,25 @@ impl JunoModule {
 		Ok(())
 	}
 
-	async fn setup_connections(&mut self) -> Result<()> {
+	// For the settings that the read loop picks up when the module connects
+	fn ensure_not_connected(&self, setting: &str) -> Result<()> {
+		if self.connected {
+			return Err(Error::Configuration(format!(
+				"{} has to be set before the module is initialized",
+				setting
+			)));
+		}
+		Ok(())
+	}
+
+	pub(crate) async fn setup_connections(&mut self) -> Result<()> {
+		if self.connected {
+			return Err(Error::Internal(String::from(
+				"Module is already connected. Modules built with JunoModule::builder() are initialized already",
+			)));
+		}
 		self.connection.setup_connection().await?;
+		self.connected = true;
 
 		// Setup the multi-threaded read-write loop
 		let data_receiver = self.connection.get_data_receiver();
@@ -186,16 +937,25 @@ impl JunoModule {
 		let protocol = BaseProtocol::from(&self.protocol);
 		let requests = self.requests.clone();
 		let functions = self.functions.clone();
-		let hook_listeners = self.hook_listeners.clone();
+		let running_calls = self.running_calls.clone();
+		let max_running_calls = self.max_running_calls;
+		let hook_dispatcher = HookDispatcher::new(
+			self.hook_listeners.clone(),
+			self.hook_history.clone(),
+			self.hook_execution,
+			self.hook_error_sink.clone(),
+		);
 
 		// Run the read-write loop
-		task::spawn(async {
+		task::spawn(async move {
 			on_data_listener(
 				data_receiver,
 				protocol,
 				requests,
 				functions,
-				hook_listeners,
+				running_calls,
+				max_running_calls,
+				hook_dispatcher,
 				write_sender,
 			)
 			.await;
@@ -205,48 +965,31 @@ impl JunoModule {
 	}
 
 	async fn send_request(&mut self, request: BaseMessage) -> Result<Value> {
-		if let BaseMessage::RegisterModuleRequest { .. } = request {
-			if self.registered {
-				let (sender, receiver) = channel::<Result<Value>>();
-				sender.send(Ok(Value::Null)).unwrap();
-
-				return match receiver.await {
-					Ok(value) => value,
-					Err(_) => Err(Error::Internal(String::from(
-						"Request sender was dropped before data could be retrieved",
-					))),
-				};
-			}
+		let is_registration = request.get_type() == request_types::REGISTER_MODULE_REQUEST;
+		if is_registration && self.registered {
+			return Ok(Value::Null);
 		}
-
-		let request_type = request.get_type();
-		let request_id = request.get_request_id().clone();
-		let mut encoded = self.protocol.encode(request);
-		if self.registered || request_type == 1 {
-			self.connection.send(encoded).await;
-		} else {
-			self.message_buffer.append(&mut encoded);
+		if self.registered || is_registration {
+			return self.request_sender().send_request(request).await;
 		}
 
-		let (sender, receiver) = channel::<Result<Value>>();
-
-		self.requests.lock().await.insert(request_id, sender);
-
-		match receiver.await {
-			Ok(value) => value,
-			Err(_) => Err(Error::Internal(String::from(
-				"Request sender was dropped before data could be retrieved",
-			))),
-		}
+		// Held back until the module is registered
+		let response = expect_response(&self.requests, request.get_request_id().clone()).await;
+		let mut encoded = self.protocol.encode(request);
+		self.message_buffer.append(&mut encoded);
+		response_of(response).await
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn on_data_listener(
 	mut receiver: UnboundedReceiver<Buffer>,
 	protocol: BaseProtocol,
 	requests: ArcRequestList,
 	functions: ArcFunctionList,
-	hook_listeners: ArcHookListenerList,
+	running_calls: ArcRunningCallList,
+	max_running_calls: Option<usize>,
+	hook_dispatcher: HookDispatcher,
 	mut write_sender: UnboundedSender<Buffer>,
 ) {
 	while let Some(data) = receiver.next().await {
@@ -256,30 +999,61 @@ async fn on_data_listener(
 
 		let value = match message {
 			BaseMessage::FunctionCallResponse { data, .. } => Ok(data),
+			BaseMessage::FunctionCallStreamChunk { data, .. } => {
+				if let Some(request) = requests.get_mut(&request_id) {
+					request.push_chunk(data);
+				}
+				continue;
+			}
+			BaseMessage::FunctionCallProgress {
+				progress, message, ..
+			} => {
+				if let Some(request) = requests.get(&request_id) {
+					request.push_progress(FunctionProgress { progress, message });
+				}
+				continue;
+			}
+			BaseMessage::FunctionCallStreamEnd { .. } => {
+				if let Some(request) = requests.remove(&request_id) {
+					request.end_stream();
+				}
+				continue;
+			}
 			BaseMessage::FunctionCallRequest { .. } => {
-				let result = execute_function_call(message, &functions).await;
-				let write_buffer = match result {
-					Ok(value) => protocol.encode(BaseMessage::FunctionCallResponse {
-						request_id: request_id.clone(),
-						data: value,
-					}),
-					Err(error) => protocol.encode(BaseMessage::Error {
-						request_id: request_id.clone(),
-						error: match error {
-							Error::Internal(_) => 0,
-							Error::FromJuno(error_code) => error_code,
-						},
-					}),
-				};
-				if let Err(err) = write_sender.send(write_buffer).await {
-					println!("Error writing back result of function call: {}", err);
+				execute_function_call(
+					message,
+					&protocol,
+					&functions,
+					&running_calls,
+					max_running_calls,
+					&mut write_sender,
+				)
+				.await;
+				Ok(Value::Null)
+			}
+			BaseMessage::CancelFunctionCallRequest { .. } => {
+				if let Some(cancel_sender) = running_calls.lock().await.remove(&request_id) {
+					cancel_sender.send(()).unwrap_or(());
 				}
 				Ok(Value::Null)
 			}
 			BaseMessage::TriggerHookRequest { .. } => {
-				execute_hook_triggered(message, &hook_listeners).await
+				execute_hook_triggered(message, &hook_dispatcher).await
 			}
-			BaseMessage::Error { error, .. } => Err(Error::FromJuno(error)),
+			BaseMessage::Error {
+				error,
+				message,
+				details,
+				source,
+				..
+			} => match source {
+				ErrorSource::Juno => Err(Error::FromJuno(error)),
+				ErrorSource::Module => Err(Error::FromModule {
+					code: error,
+					message,
+					details,
+				}),
+			},
 			_ => Ok(Value::Null),
 		};
 
@@ -287,44 +1061,244 @@ async fn on_data_listener(
 			drop(requests);
 			continue;
 		}
-		if requests.remove(&request_id).unwrap().send(value).is_err() {
+		if requests
+			.remove(&request_id)
+			.unwrap()
+			.resolve(value)
+			.is_err()
+		{
 			println!("Error sending response of requestId: {}", &request_id);
 		}
 		drop(requests);
 	}
+
+	// The connection is gone, so nothing that's waiting on juno will ever hear back.
+	// Sends fail from now on, and the requests that were already sent fail right away
+	write_sender.close_channel();
+	for (_, request) in requests.lock().await.drain() {
+		request.resolve(Err(connection_closed())).unwrap_or(());
+	}
 }
 
-async fn execute_function_call(message: BaseMessage, functions: &ArcFunctionList) -> Result<Value> {
+async fn execute_function_call(
+	message: BaseMessage,
+	protocol: &BaseProtocol,
+	functions: &ArcFunctionList,
+	running_calls: &ArcRunningCallList,
+	max_running_calls: Option<usize>,
+	write_sender: &mut UnboundedSender<Buffer>,
+) {
 	if let BaseMessage::FunctionCallRequest {
+		request_id,
 		function,
 		arguments,
-		..
 	} = message
 	{
-		let functions = functions.lock().await;
-		if !functions.contains_key(&function) {
-			return Err(Error::FromJuno(utils::errors::UNKNOWN_FUNCTION));
+		let handler = functions.lock().await.get(&function).cloned();
+		if let (Some(handler), Some(max_running_calls)) = (&handler, max_running_calls) {
+			if handler.runs_on_task() && running_calls.lock().await.len() >= max_running_calls {
+				let error = Error::FromModule {
+					code: utils::errors::TOO_MANY_CALLS,
+					message: Some(format!(
+						"Too many calls running. At most {} can run at once",
+						max_running_calls
+					)),
+					details: Value::Null,
+				};
+				send_function_response(protocol, write_sender, request_id, Err(error)).await;
+				return;
+			}
 		}
-		Ok(functions[&function](arguments))
+		let result = match handler {
+			None => Err(Error::FromJuno(utils::errors::UNKNOWN_FUNCTION)),
+			Some(FunctionHandler::Infallible(function)) => Ok(function(arguments)),
+			Some(FunctionHandler::Fallible(function)) => function(arguments),
+			Some(handler) => {
+				run_on_task(
+					handler,
+					request_id,
+					function,
+					arguments,
+					protocol,
+					write_sender,
+					running_calls,
+				)
+				.await;
+				return;
+			}
+		};
+		send_function_response(protocol, write_sender, request_id, result).await;
 	} else {
 		panic!("Cannot execute function from a request that wasn't a FunctionCallRequest!");
 	}
 }
 
+// Runs a call to a function that doesn't respond right away. It's tracked as a running call
+// until it's responded to, so that it can be cancelled
+async fn run_on_task(
+	handler: FunctionHandler,
+	request_id: String,
+	function: String,
+	arguments: HashMap<String, Value>,
+	protocol: &BaseProtocol,
+	write_sender: &mut UnboundedSender<Buffer>,
+	running_calls: &ArcRunningCallList,
+) {
+	let protocol = Arc::new(BaseProtocol::from(protocol));
+	let context = start_running_call(
+		request_id.clone(),
+		function,
+		&protocol,
+		write_sender,
+		running_calls,
+	)
+	.await;
+	match handler {
+		FunctionHandler::Async(function_handler) => {
+			let running_calls = running_calls.clone();
+			let mut write_sender = write_sender.clone();
+			task::spawn(async move {
+				let result = function_handler(context, arguments).await;
+				finish_running_call(
+					&protocol,
+					&mut write_sender,
+					&running_calls,
+					request_id,
+					result,
+				)
+				.await;
+			});
+		}
+		FunctionHandler::Incoming(call_sender) => {
+			let responder = Responder::new(
+				request_id,
+				protocol,
+				write_sender.clone(),
+				running_calls.clone(),
+				None,
+			);
+			let call = IncomingCall::new(context, arguments, responder);
+			// If nobody is reading the incoming calls anymore, dropping the call fails it
+			call_sender.unbounded_send(call).unwrap_or(());
+		}
+		FunctionHandler::Deferred {
+			function: function_handler,
+			timeout,
+		} => {
+			let responder = Responder::new(
+				request_id,
+				protocol,
+				write_sender.clone(),
+				running_calls.clone(),
+				timeout,
+			);
+			function_handler(context, arguments, responder);
+		}
+		FunctionHandler::Stream(function_handler) => {
+			let running_calls = running_calls.clone();
+			let mut write_sender = write_sender.clone();
+			task::spawn(async move {
+				let mut stream = function_handler(context.clone(), arguments);
+				while let Some(data) = stream.next().await {
+					// Stop pulling from the stream once the caller isn't listening anymore
+					if context.is_cancelled() {
+						break;
+					}
+					let write_buffer = protocol.encode(BaseMessage::FunctionCallStreamChunk {
+						request_id: request_id.clone(),
+						data,
+					});
+					if let Err(err) = write_sender.send(write_buffer).await {
+						println!("Error writing back chunk of function call: {}", err);
+					}
+				}
+				if running_calls.lock().await.remove(&request_id).is_none() {
+					return;
+				}
+				let write_buffer =
+					protocol.encode(BaseMessage::FunctionCallStreamEnd { request_id });
+				if let Err(err) = write_sender.send(write_buffer).await {
+					println!("Error writing back end of function call stream: {}", err);
+				}
+			});
+		}
+		FunctionHandler::Infallible(_) | FunctionHandler::Fallible(_) => {
+			unreachable!("Functions that respond right away don't run on a task")
+		}
+	}
+}
+
+// Keeps track of a function call that runs on its own task, so that it can be cancelled
+async fn start_running_call(
+	request_id: String,
+	function: String,
+	protocol: &Arc<BaseProtocol>,
+	write_sender: &UnboundedSender<Buffer>,
+	running_calls: &ArcRunningCallList,
+) -> FunctionContext {
+	let (context, cancel_sender) = FunctionContext::new(
+		request_id.clone(),
+		function,
+		protocol.clone(),
+		write_sender.clone(),
+	);
+	running_calls.lock().await.insert(request_id, cancel_sender);
+	context
+}
+
 async fn execute_hook_triggered(
 	message: BaseMessage,
-	hook_listeners: &ArcHookListenerList,
+	hook_dispatcher: &HookDispatcher,
 ) -> Result<Value> {
-	if let BaseMessage::TriggerHookRequest { hook, .. } = message {
-		let hook_listeners = hook_listeners.lock().await;
-		if !hook_listeners.contains_key(&hook) {
-			todo!("Wtf do I do now? Need to propogate errors. How do I do that?");
-		}
-		for listener in &hook_listeners[&hook] {
-			listener(Value::Null);
-		}
+	if let BaseMessage::TriggerHookRequest { hook, data, .. } = message {
+		hook_dispatcher.trigger(hook, data).await;
 	} else {
 		panic!("Cannot execute hook from a request that wasn't a TriggerHookRequest!");
 	}
 	Ok(Value::Null)
 }
+
+// Explains why juno most likely turned the registration down.
+// Juno doesn't have a code of its own for unmet dependencies, so anything that isn't about the
+// module id is put down to the version or the dependency requirements
+fn registration_error(
+	module_id: &str,
+	version: &str,
+	dependencies: &[Dependency],
+	error: Error,
+) -> Error {
+	let (code, reason) = match error {
+		Error::FromJuno(code) => (code, None),
+		Error::FromModule { code, message, .. } => (code, message),
+		error => return error,
+	};
+	let message = match code {
+		errors::INVALID_MODULE_ID => format!("'{}' isn't a valid module id", module_id),
+		errors::DUPLICATE_MODULE => {
+			format!("A module with the id '{}' is already registered", module_id)
+		}
+		_ => {
+			let mut requirements = dependencies
+				.iter()
+				.map(Dependency::to_string)
+				.collect::<Vec<_>>();
+			requirements.sort();
+			let requirements = if requirements.is_empty() {
+				String::from("no dependencies")
+			} else {
+				requirements.join(", ")
+			};
+			format!(
+				"{} {} wasn't accepted. Check that its dependencies ({}) are met by the modules registered with juno",
+				module_id, version, requirements
+			)
+		}
+	};
+	Error::Registration {
+		code,
+		message: match reason {
+			Some(reason) => format!("{} ({})", message, reason),
+			None => message,
+		},
+	}
+}
@@ -0,0 +1,320 @@
+use crate::{
+	connection::{BaseConnection, InetSocketConnection},
+	functions::{CircuitBreakerPolicy, RetryPolicy},
+	hooks::{HookErrorSink, HookExecution},
+	models::{parse_version, Dependency},
+	protocol::BaseProtocol,
+	utils::{Error, Result},
+	JunoModule,
+};
+
+#[cfg(target_family = "unix")]
+use crate::connection::UnixSocketConnection;
+
+use async_std::{future, task};
+use std::{
+	collections::HashMap,
+	net::{AddrParseError, SocketAddr},
+	sync::Arc,
+	time::Duration,
+};
+
+// How the first connection to juno is retried when juno isn't reachable yet.
+// A module that loses its connection later on isn't reconnected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectRetryPolicy {
+	pub attempts: u32,
+	pub delay: Duration,
+}
+
+impl ConnectRetryPolicy {
+	// Connects once, and fails right away if juno isn't reachable
+	pub fn never() -> Self {
+		ConnectRetryPolicy {
+			attempts: 0,
+			delay: Duration::from_secs(0),
+		}
+	}
+
+	pub fn fixed(attempts: u32, delay: Duration) -> Self {
+		ConnectRetryPolicy { attempts, delay }
+	}
+}
+
+impl Default for ConnectRetryPolicy {
+	fn default() -> Self {
+		Self::never()
+	}
+}
+
+enum Transport {
+	// A socket path or an ip:port, decided the same way as JunoModule::default
+	Address(String),
+	UnixSocket(String),
+	InetSocket(String, u16),
+	Connection(Box<dyn BaseConnection + Send + Sync>),
+}
+
+// Configures a module, then connects it to juno and registers it in one go
+pub struct JunoModuleBuilder {
+	transport: Option<Transport>,
+	protocol: Option<BaseProtocol>,
+	module_id: Option<String>,
+	version: Option<String>,
+	dependencies: HashMap<String, String>,
+	call_timeout: Option<Duration>,
+	registration_timeout: Option<Duration>,
+	connect_retry_policy: ConnectRetryPolicy,
+	max_running_calls: Option<usize>,
+	retry_policy: Option<RetryPolicy>,
+	circuit_breaker: Option<CircuitBreakerPolicy>,
+	hook_execution: HookExecution,
+	hook_error_sink: Option<HookErrorSink>,
+}
+
+impl JunoModuleBuilder {
+	pub(crate) fn new() -> Self {
+		JunoModuleBuilder {
+			transport: None,
+			protocol: None,
+			module_id: None,
+			version: None,
+			dependencies: HashMap::new(),
+			call_timeout: None,
+			registration_timeout: None,
+			connect_retry_policy: ConnectRetryPolicy::default(),
+			max_running_calls: None,
+			retry_policy: None,
+			circuit_breaker: None,
+			hook_execution: HookExecution::default(),
+			hook_error_sink: None,
+		}
+	}
+
+	pub fn address(mut self, address: &str) -> Self {
+		self.transport = Some(Transport::Address(address.to_string()));
+		self
+	}
+
+	pub fn unix_socket(mut self, socket_path: &str) -> Self {
+		self.transport = Some(Transport::UnixSocket(socket_path.to_string()));
+		self
+	}
+
+	pub fn inet_socket(mut self, host: &str, port: u16) -> Self {
+		self.transport = Some(Transport::InetSocket(host.to_string(), port));
+		self
+	}
+
+	pub fn connection(mut self, connection: Box<dyn BaseConnection + Send + Sync>) -> Self {
+		self.transport = Some(Transport::Connection(connection));
+		self
+	}
+
+	pub fn protocol(mut self, protocol: BaseProtocol) -> Self {
+		self.protocol = Some(protocol);
+		self
+	}
+
+	pub fn module_id(mut self, module_id: &str) -> Self {
+		self.module_id = Some(module_id.to_string());
+		self
+	}
+
+	pub fn version(mut self, version: &str) -> Self {
+		self.version = Some(version.to_string());
+		self
+	}
+
+	pub fn dependency(mut self, module_id: &str, version: &str) -> Self {
+		self.dependencies
+			.insert(module_id.to_string(), version.to_string());
+		self
+	}
+
+	pub fn require(mut self, dependency: Dependency) -> Self {
+		self.dependencies.insert(
+			dependency.get_module_id().to_string(),
+			dependency.get_requirement().to_string(),
+		);
+		self
+	}
+
+	pub fn dependencies(mut self, dependencies: HashMap<String, String>) -> Self {
+		self.dependencies.extend(dependencies);
+		self
+	}
+
+	// Every call the module makes fails with Error::Timeout if it isn't responded to in time
+	pub fn call_timeout(mut self, timeout: Duration) -> Self {
+		self.call_timeout = Some(timeout);
+		self
+	}
+
+	// How long juno has to accept the module's registration
+	pub fn registration_timeout(mut self, timeout: Duration) -> Self {
+		self.registration_timeout = Some(timeout);
+		self
+	}
+
+	pub fn connect_retry_policy(mut self, connect_retry_policy: ConnectRetryPolicy) -> Self {
+		self.connect_retry_policy = connect_retry_policy;
+		self
+	}
+
+	// Incoming calls that would run on their own task are failed once this many are running
+	pub fn max_running_calls(mut self, max_running_calls: usize) -> Self {
+		self.max_running_calls = Some(max_running_calls);
+		self
+	}
+
+	// Every call_function of the module, delayed ones included, is retried according to the policy
+	pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+		self.retry_policy = Some(retry_policy);
+		self
+	}
+
+	// Calls to a module that keeps failing fail fast until the cooldown is up
+	pub fn circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+		self.circuit_breaker = Some(policy);
+		self
+	}
+
+	pub fn hook_execution(mut self, execution: HookExecution) -> Self {
+		self.hook_execution = execution;
+		self
+	}
+
+	// Receives the errors of hook listeners instead of them being printed
+	pub fn hook_error_sink<F>(mut self, error_sink: F) -> Self
+	where
+		F: Fn(&str, Error) + Send + Sync + 'static,
+	{
+		self.hook_error_sink = Some(Arc::new(error_sink));
+		self
+	}
+
+	// Validates the configuration, then returns the connected and registered module
+	pub async fn build(self) -> Result<JunoModule> {
+		let module_id = match self.module_id {
+			Some(module_id) => module_id,
+			None => return Err(configuration_error("A module id is required")),
+		};
+		if module_id.is_empty() || module_id.contains(|c: char| c == '.' || c.is_whitespace()) {
+			return Err(configuration_error(&format!(
+				"Module id '{}' must be non-empty, without periods or whitespace",
+				module_id
+			)));
+		}
+		let version = match self.version {
+			Some(version) if !version.is_empty() => version,
+			_ => return Err(configuration_error("A version is required")),
+		};
+		parse_version(&module_id, &version)?;
+		for (dependency, requirement) in &self.dependencies {
+			Dependency::new(dependency, requirement)?;
+		}
+		if self
+			.retry_policy
+			.as_ref()
+			.is_some_and(|retry_policy| retry_policy.max_attempts == 0)
+		{
+			return Err(configuration_error(
+				"A retry policy has to make at least one attempt",
+			));
+		}
+		if self
+			.circuit_breaker
+			.is_some_and(|policy| policy.failure_threshold == 0)
+		{
+			return Err(configuration_error(
+				"A circuit breaker has to allow at least one failure",
+			));
+		}
+		if self.circuit_breaker.is_some() && self.call_timeout.is_none() {
+			return Err(configuration_error(CIRCUIT_BREAKER_WITHOUT_TIMEOUT));
+		}
+		if self.max_running_calls == Some(0) {
+			return Err(configuration_error(
+				"At least one call has to be allowed to run at once",
+			));
+		}
+		let connection = match self.transport {
+			Some(transport) => connection_for(transport)?,
+			None => return Err(configuration_error("An address to reach juno is required")),
+		};
+
+		let mut module = JunoModule::new(self.protocol.unwrap_or_default(), connection);
+		module.set_hook_execution(self.hook_execution)?;
+		if let Some(error_sink) = self.hook_error_sink {
+			module.set_hook_error_sink(move |hook, err| error_sink(hook, err))?;
+		}
+		if let Some(timeout) = self.call_timeout {
+			module.set_call_timeout(timeout);
+		}
+		if let Some(max_running_calls) = self.max_running_calls {
+			module.set_max_running_calls(max_running_calls)?;
+		}
+		if let Some(retry_policy) = self.retry_policy {
+			module.set_retry_policy(retry_policy);
+		}
+		if let Some(policy) = self.circuit_breaker {
+			module.set_circuit_breaker(policy)?;
+		}
+
+		let mut attempt = 0;
+		while let Err(err) = module.setup_connections().await {
+			if attempt >= self.connect_retry_policy.attempts {
+				return Err(err);
+			}
+			attempt += 1;
+			task::sleep(self.connect_retry_policy.delay).await;
+		}
+
+		let registration = module.register(&module_id, &version, self.dependencies);
+		match self.registration_timeout {
+			Some(timeout) => match future::timeout(timeout, registration).await {
+				Ok(result) => result?,
+				Err(_) => return Err(Error::Timeout),
+			},
+			None => registration.await?,
+		}
+		Ok(module)
+	}
+}
+
+pub(crate) const CIRCUIT_BREAKER_WITHOUT_TIMEOUT: &str =
+	"A circuit breaker needs a call timeout, or calls to a module that stopped responding never fail";
+
+fn configuration_error(message: &str) -> Error {
+	Error::Configuration(String::from(message))
+}
+
+fn connection_for(transport: Transport) -> Result<Box<dyn BaseConnection + Send + Sync>> {
+	Ok(match transport {
+		Transport::Address(address) => {
+			let is_ip: std::result::Result<SocketAddr, AddrParseError> = address.parse();
+			match is_ip {
+				Ok(ip) => Box::new(InetSocketConnection::new(format!("{}", ip))),
+				Err(_) => unix_socket_connection(address)?,
+			}
+		}
+		Transport::UnixSocket(socket_path) => unix_socket_connection(socket_path)?,
+		Transport::InetSocket(host, port) => {
+			Box::new(InetSocketConnection::new(format!("{}:{}", host, port)))
+		}
+		Transport::Connection(connection) => connection,
+	})
+}
+
+#[cfg(target_family = "unix")]
+fn unix_socket_connection(socket_path: String) -> Result<Box<dyn BaseConnection + Send + Sync>> {
+	Ok(Box::new(UnixSocketConnection::new(socket_path)))
+}
+
+#[cfg(target_family = "windows")]
+fn unix_socket_connection(_: String) -> Result<Box<dyn BaseConnection + Send + Sync>> {
+	Err(configuration_error(
+		"Unix sockets are not supported on windows",
+	))
+}
@@ -0,0 +1,166 @@
+use crate::{
+	hooks::HookExecution,
+	utils::{Error, Result},
+	ConnectRetryPolicy, JunoModule, JunoModuleBuilder,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, env, fs, path::Path, str::FromStr, time::Duration};
+
+// Where the module looks for juno when nothing else says otherwise
+pub const DEFAULT_ADDRESS: &str = "./juno.sock";
+
+// Everything a module can be configured with from the outside.
+// In a config file, these are the keys. In the environment, they're upper-cased and
+// prefixed with JUNO_ (like JUNO_MODULE_ID), and the dependencies are a comma separated
+// list of `module=requirement` pairs
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ModuleConfig {
+	address: Option<String>,
+	module_id: Option<String>,
+	version: Option<String>,
+	dependencies: HashMap<String, String>,
+	call_timeout_ms: Option<u64>,
+	registration_timeout_ms: Option<u64>,
+	connect_attempts: Option<u32>,
+	connect_retry_delay_ms: Option<u64>,
+	max_running_calls: Option<usize>,
+	hook_execution: Option<String>,
+}
+
+impl ModuleConfig {
+	// Json files are read as json, everything else as toml
+	pub fn from_file(path: &Path) -> Result<Self> {
+		let contents = fs::read_to_string(path).map_err(|err| {
+			Error::Configuration(format!("Couldn't read {}: {}", path.display(), err))
+		})?;
+		let is_json = path
+			.extension()
+			.is_some_and(|extension| extension == "json");
+		let config = if is_json {
+			serde_json::from_str(&contents).map_err(|err| err.to_string())
+		} else {
+			toml::from_str(&contents).map_err(|err| err.to_string())
+		};
+		config.map_err(|err| Error::Configuration(format!("Invalid {}: {}", path.display(), err)))
+	}
+
+	// Variables that aren't valid unicode are left out, the same as unset ones
+	pub fn from_env() -> Result<Self> {
+		let vars = env::vars_os()
+			.filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+			.collect();
+		Self::from_vars(&vars)
+	}
+
+	// Same as from_env, with the variables looked up in the map instead
+	pub fn from_vars(vars: &HashMap<String, String>) -> Result<Self> {
+		let mut dependencies = HashMap::new();
+		if let Some(list) = env_var(vars, "JUNO_DEPENDENCIES") {
+			for dependency in list
+				.split(',')
+				.map(str::trim)
+				.filter(|item| !item.is_empty())
+			{
+				let mut parts = dependency.splitn(2, '=');
+				match (parts.next(), parts.next()) {
+					(Some(module_id), Some(requirement)) => {
+						dependencies
+							.insert(module_id.trim().to_string(), requirement.trim().to_string());
+					}
+					_ => {
+						return Err(Error::Configuration(format!(
+							"Dependency '{}' in JUNO_DEPENDENCIES isn't of the form module=requirement",
+							dependency
+						)))
+					}
+				}
+			}
+		}
+
+		Ok(ModuleConfig {
+			address: env_var(vars, "JUNO_ADDRESS"),
+			module_id: env_var(vars, "JUNO_MODULE_ID"),
+			version: env_var(vars, "JUNO_VERSION"),
+			dependencies,
+			call_timeout_ms: parsed_env_var(vars, "JUNO_CALL_TIMEOUT_MS")?,
+			registration_timeout_ms: parsed_env_var(vars, "JUNO_REGISTRATION_TIMEOUT_MS")?,
+			connect_attempts: parsed_env_var(vars, "JUNO_CONNECT_ATTEMPTS")?,
+			connect_retry_delay_ms: parsed_env_var(vars, "JUNO_CONNECT_RETRY_DELAY_MS")?,
+			max_running_calls: parsed_env_var(vars, "JUNO_MAX_RUNNING_CALLS")?,
+			hook_execution: env_var(vars, "JUNO_HOOK_EXECUTION"),
+		})
+	}
+
+	// Whatever is set in the other config takes precedence
+	pub fn merge(mut self, other: ModuleConfig) -> Self {
+		self.address = other.address.or(self.address);
+		self.module_id = other.module_id.or(self.module_id);
+		self.version = other.version.or(self.version);
+		self.dependencies.extend(other.dependencies);
+		self.call_timeout_ms = other.call_timeout_ms.or(self.call_timeout_ms);
+		self.registration_timeout_ms = other
+			.registration_timeout_ms
+			.or(self.registration_timeout_ms);
+		self.connect_attempts = other.connect_attempts.or(self.connect_attempts);
+		self.connect_retry_delay_ms = other.connect_retry_delay_ms.or(self.connect_retry_delay_ms);
+		self.max_running_calls = other.max_running_calls.or(self.max_running_calls);
+		self.hook_execution = other.hook_execution.or(self.hook_execution);
+		self
+	}
+
+	pub fn into_builder(self) -> Result<JunoModuleBuilder> {
+		let mut builder = JunoModule::builder()
+			.address(self.address.as_deref().unwrap_or(DEFAULT_ADDRESS))
+			.dependencies(self.dependencies);
+		if let Some(module_id) = &self.module_id {
+			builder = builder.module_id(module_id);
+		}
+		if let Some(version) = &self.version {
+			builder = builder.version(version);
+		}
+		if let Some(timeout) = self.call_timeout_ms {
+			builder = builder.call_timeout(Duration::from_millis(timeout));
+		}
+		if let Some(timeout) = self.registration_timeout_ms {
+			builder = builder.registration_timeout(Duration::from_millis(timeout));
+		}
+		if let Some(attempts) = self.connect_attempts {
+			let delay = Duration::from_millis(self.connect_retry_delay_ms.unwrap_or(1000));
+			builder = builder.connect_retry_policy(ConnectRetryPolicy::fixed(attempts, delay));
+		}
+		if let Some(max_running_calls) = self.max_running_calls {
+			builder = builder.max_running_calls(max_running_calls);
+		}
+		if let Some(hook_execution) = &self.hook_execution {
+			builder = builder.hook_execution(match hook_execution.as_str() {
+				"sequential" => HookExecution::Sequential,
+				"concurrent" => HookExecution::Concurrent,
+				other => {
+					return Err(Error::Configuration(format!(
+						"Hook execution '{}' has to be either sequential or concurrent",
+						other
+					)))
+				}
+			});
+		}
+		Ok(builder)
+	}
+}
+
+fn env_var(vars: &HashMap<String, String>, name: &str) -> Option<String> {
+	vars.get(name).filter(|value| !value.is_empty()).cloned()
+}
+
+fn parsed_env_var<T: FromStr>(vars: &HashMap<String, String>, name: &str) -> Result<Option<T>> {
+	match env_var(vars, name) {
+		Some(value) => match value.parse() {
+			Ok(parsed) => Ok(Some(parsed)),
+			Err(_) => Err(Error::Configuration(format!(
+				"{} has an invalid value: {}",
+				name, value
+			))),
+		},
+		None => Ok(None),
+	}
+}
@@ -1,12 +1,19 @@
 mod juno_module;
+mod juno_module_builder;
+mod juno_module_config;
 mod utils;
 
 pub mod connection;
+pub mod functions;
+pub mod hooks;
 pub mod models;
 pub mod protocol;
 
 #[macro_use]
 pub mod macros;
 
+pub use juno_macros::{function, hook};
 pub use juno_module::{json, JunoModule};
-pub use utils::{Error, Result};
+pub use juno_module_builder::{ConnectRetryPolicy, JunoModuleBuilder};
+pub use juno_module_config::DEFAULT_ADDRESS;
+pub use utils::{errors, Error, Result};
@@ -1,3 +1,10 @@
+use crate::{
+	models::Value,
+	utils::{errors, Error, Result},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+
 #[macro_export(local_inner_macros)]
 macro_rules! value {
     // Hide distracting implementation details from the generated rustdoc.
@@ -5,3 +12,34 @@ macro_rules! value {
         json!($($kv)+).into()
     };
 }
+
+// Used by the code #[juno::function] generates. Not meant to be called directly
+#[doc(hidden)]
+pub fn extract_argument<T: DeserializeOwned>(
+	arguments: &mut HashMap<String, Value>,
+	name: &str,
+) -> Result<T> {
+	// A missing argument is null, so that it can still be deserialized into an Option
+	let argument = arguments.remove(name).unwrap_or(Value::Null);
+	argument.deserialize().map_err(|err| {
+		Error::from_module(
+			errors::MALFORMED_REQUEST,
+			&format!("Invalid argument {}: {}", name, err),
+		)
+	})
+}
+
+#[doc(hidden)]
+pub fn convert_value<T: Serialize>(value: T) -> Result<Value> {
+	Value::serialize(&value)
+}
+
+#[doc(hidden)]
+pub fn convert_result<T: Serialize, E: Into<Error>>(
+	result: std::result::Result<T, E>,
+) -> Result<Value> {
+	match result {
+		Ok(value) => Value::serialize(&value),
+		Err(err) => Err(err.into()),
+	}
+}
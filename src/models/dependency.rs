@@ -0,0 +1,54 @@
+use crate::utils::{Error, Result};
+use semver::{Version, VersionReq};
+use std::fmt::{Display, Formatter};
+
+// A module this module depends on, along with the semver range of versions it works with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+	module_id: String,
+	requirement: VersionReq,
+}
+
+impl Dependency {
+	// Fails with Error::Configuration if the requirement isn't a valid semver range
+	pub fn new(module_id: &str, requirement: &str) -> Result<Self> {
+		match VersionReq::parse(requirement) {
+			Ok(requirement) => Ok(Dependency {
+				module_id: String::from(module_id),
+				requirement,
+			}),
+			Err(err) => Err(Error::Configuration(format!(
+				"Requirement '{}' on module '{}' isn't a valid semver range: {}",
+				requirement, module_id, err
+			))),
+		}
+	}
+
+	pub fn get_module_id(&self) -> &str {
+		&self.module_id
+	}
+
+	pub fn get_requirement(&self) -> &VersionReq {
+		&self.requirement
+	}
+
+	pub fn matches(&self, version: &Version) -> bool {
+		self.requirement.matches(version)
+	}
+}
+
+impl Display for Dependency {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}", self.module_id, self.requirement)
+	}
+}
+
+// Fails with Error::Configuration if the version isn't valid semver
+pub(crate) fn parse_version(module_id: &str, version: &str) -> Result<Version> {
+	Version::parse(version).map_err(|err| {
+		Error::Configuration(format!(
+			"Version '{}' of module '{}' isn't valid semver: {}",
+			version, module_id, err
+		))
+	})
+}
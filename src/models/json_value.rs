@@ -1,6 +1,29 @@
-use crate::models::{Number, Value};
+use crate::{
+	models::{Number, Value},
+	utils::{Error, Result},
+};
+use serde::{de::DeserializeOwned, Serialize};
 use std::{collections::HashMap, convert::From};
 
+impl Value {
+	// Converts anything serde can serialize into a Value, going through its json representation
+	pub fn serialize<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+		match serde_json::to_value(value) {
+			Ok(json_value) => Ok(json_value.into()),
+			Err(err) => Err(Error::Internal(format!(
+				"Value couldn't be serialized: {}",
+				err
+			))),
+		}
+	}
+
+	pub fn deserialize<T: DeserializeOwned>(self) -> Result<T> {
+		let json_value: serde_json::Value = self.into();
+		serde_json::from_value(json_value)
+			.map_err(|err| Error::Internal(format!("Value couldn't be deserialized: {}", err)))
+	}
+}
+
 impl From<serde_json::Number> for Number {
 	fn from(value: serde_json::Number) -> Self {
 		if value.is_f64() {
@@ -13,9 +36,9 @@ impl From<serde_json::Number> for Number {
 	}
 }
 
-impl Into<serde_json::Number> for Number {
-	fn into(self) -> serde_json::Number {
-		match self {
+impl From<Number> for serde_json::Number {
+	fn from(value: Number) -> Self {
+		match value {
 			Number::NegInt(s) => serde_json::Number::from(s),
 			Number::PosInt(u) => serde_json::Number::from(u),
 			Number::Float(d) => serde_json::Number::from_f64(d).unwrap(),
@@ -48,9 +71,9 @@ impl From<serde_json::Value> for Value {
 	}
 }
 
-impl Into<serde_json::Value> for Value {
-	fn into(self) -> serde_json::Value {
-		match self {
+impl From<Value> for serde_json::Value {
+	fn from(v: Value) -> Self {
+		match v {
 			Value::Null => serde_json::Value::Null,
 			Value::Bool(b) => serde_json::Value::Bool(b),
 			Value::Number(n) => serde_json::Value::Number(n.into()),
@@ -1,6 +1,14 @@
 use crate::{models::Value, utils::request_types};
 use std::collections::HashMap;
 
+// Who an error came from. Errors from juno itself don't say, so that's the default
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ErrorSource {
+	#[default]
+	Juno,
+	Module,
+}
+
 pub enum BaseMessage {
 	RegisterModuleRequest {
 		request_id: String,
@@ -30,6 +38,7 @@ pub enum BaseMessage {
 	TriggerHookRequest {
 		request_id: String,
 		hook: String,
+		data: Value,
 	},
 	TriggerHookResponse {
 		request_id: String,
@@ -42,9 +51,42 @@ pub enum BaseMessage {
 		request_id: String,
 		function: String,
 	},
+	CancelFunctionCallRequest {
+		request_id: String,
+	},
+	FunctionCallStreamChunk {
+		request_id: String,
+		data: Value,
+	},
+	FunctionCallStreamEnd {
+		request_id: String,
+	},
+	FunctionCallProgress {
+		request_id: String,
+		progress: f64,
+		message: Option<String>,
+	},
+	UnregisterHookRequest {
+		request_id: String,
+		hook: String,
+	},
+	UnregisterHookResponse {
+		request_id: String,
+	},
+	UndeclareFunctionRequest {
+		request_id: String,
+		function: String,
+	},
+	UndeclareFunctionResponse {
+		request_id: String,
+		function: String,
+	},
 	Error {
 		request_id: String,
 		error: u32,
+		message: Option<String>,
+		details: Value,
+		source: ErrorSource,
 	},
 	Unknown {
 		request_id: String,
@@ -65,6 +107,22 @@ impl BaseMessage {
 			BaseMessage::TriggerHookResponse { .. } => 8,
 			BaseMessage::DeclareFunctionRequest { .. } => 9,
 			BaseMessage::DeclareFunctionResponse { .. } => 10,
+			BaseMessage::CancelFunctionCallRequest { .. } => {
+				request_types::CANCEL_FUNCTION_CALL_REQUEST
+			}
+			BaseMessage::FunctionCallStreamChunk { .. } => {
+				request_types::FUNCTION_CALL_STREAM_CHUNK
+			}
+			BaseMessage::FunctionCallStreamEnd { .. } => request_types::FUNCTION_CALL_STREAM_END,
+			BaseMessage::FunctionCallProgress { .. } => request_types::FUNCTION_CALL_PROGRESS,
+			BaseMessage::UnregisterHookRequest { .. } => request_types::UNREGISTER_HOOK_REQUEST,
+			BaseMessage::UnregisterHookResponse { .. } => request_types::UNREGISTER_HOOK_RESPONSE,
+			BaseMessage::UndeclareFunctionRequest { .. } => {
+				request_types::UNDECLARE_FUNCTION_REQUEST
+			}
+			BaseMessage::UndeclareFunctionResponse { .. } => {
+				request_types::UNDECLARE_FUNCTION_RESPONSE
+			}
 		}
 	}
 
@@ -82,6 +140,14 @@ impl BaseMessage {
 			BaseMessage::TriggerHookResponse { request_id, .. } => request_id,
 			BaseMessage::DeclareFunctionRequest { request_id, .. } => request_id,
 			BaseMessage::DeclareFunctionResponse { request_id, .. } => request_id,
+			BaseMessage::CancelFunctionCallRequest { request_id } => request_id,
+			BaseMessage::FunctionCallStreamChunk { request_id, .. } => request_id,
+			BaseMessage::FunctionCallStreamEnd { request_id } => request_id,
+			BaseMessage::FunctionCallProgress { request_id, .. } => request_id,
+			BaseMessage::UnregisterHookRequest { request_id, .. } => request_id,
+			BaseMessage::UnregisterHookResponse { request_id } => request_id,
+			BaseMessage::UndeclareFunctionRequest { request_id, .. } => request_id,
+			BaseMessage::UndeclareFunctionResponse { request_id, .. } => request_id,
 		}
 	}
 }
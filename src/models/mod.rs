@@ -1,6 +1,10 @@
+mod dependency;
 mod json_value;
 mod messages;
 mod value;
 
-pub use messages::BaseMessage;
+pub(crate) use dependency::parse_version;
+pub use dependency::Dependency;
+pub use messages::{BaseMessage, ErrorSource};
+pub use semver::{Version, VersionReq};
 pub use value::{Number, Value};
@@ -9,11 +9,7 @@ pub enum Number {
 
 impl Number {
 	pub fn is_i64(&self) -> bool {
-		if let Number::NegInt(_) = self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Number::NegInt(_))
 	}
 
 	pub fn as_i64(&self) -> Option<i64> {
@@ -25,11 +21,7 @@ impl Number {
 	}
 
 	pub fn is_u64(&self) -> bool {
-		if let Number::PosInt(_) = self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Number::PosInt(_))
 	}
 
 	pub fn as_u64(&self) -> Option<u64> {
@@ -41,11 +33,7 @@ impl Number {
 	}
 
 	pub fn is_f64(&self) -> bool {
-		if let Number::Float(_) = self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Number::Float(_))
 	}
 
 	pub fn as_f64(&self) -> Option<f64> {
@@ -69,11 +57,7 @@ pub enum Value {
 
 impl Value {
 	pub fn is_null(&self) -> bool {
-		if let Value::Null = &self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Value::Null)
 	}
 
 	pub fn as_null(&self) -> Option<()> {
@@ -85,11 +69,7 @@ impl Value {
 	}
 
 	pub fn is_bool(&self) -> bool {
-		if let Value::Bool(_) = &self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Value::Bool(_))
 	}
 
 	pub fn as_bool(&self) -> Option<&bool> {
@@ -101,11 +81,7 @@ impl Value {
 	}
 
 	pub fn is_number(&self) -> bool {
-		if let Value::Number(_) = &self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Value::Number(_))
 	}
 
 	pub fn as_number(&self) -> Option<&Number> {
@@ -117,11 +93,7 @@ impl Value {
 	}
 
 	pub fn is_string(&self) -> bool {
-		if let Value::String(_) = &self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Value::String(_))
 	}
 
 	pub fn as_string(&self) -> Option<&String> {
@@ -133,11 +105,7 @@ impl Value {
 	}
 
 	pub fn is_array(&self) -> bool {
-		if let Value::Array(_) = &self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Value::Array(_))
 	}
 
 	pub fn as_array(&self) -> Option<&Vec<Value>> {
@@ -149,11 +117,7 @@ impl Value {
 	}
 
 	pub fn is_object(&self) -> bool {
-		if let Value::Object(_) = &self {
-			true
-		} else {
-			false
-		}
+		matches!(self, Value::Object(_))
 	}
 
 	pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
@@ -6,19 +6,25 @@ use crate::{
 
 use std::{
 	collections::HashMap,
+	sync::atomic::{AtomicU64, Ordering},
 	time::{SystemTime, UNIX_EPOCH},
 };
 
+// Calls made at once can read the same time, so the counter is what keeps their ids apart
+static NEXT_REQUEST_NUMBER: AtomicU64 = AtomicU64::new(0);
+
 pub enum BaseProtocol {
 	JsonProtocol { module_id: String },
 	MsgPackProtocol { module_id: String },
 }
 
-impl BaseProtocol {
-	pub fn default() -> Self {
+impl Default for BaseProtocol {
+	fn default() -> Self {
 		json_protocol::default()
 	}
+}
 
+impl BaseProtocol {
 	pub fn from(other: &Self) -> Self {
 		match other {
 			BaseProtocol::JsonProtocol { .. } => json_protocol::from(other),
@@ -28,12 +34,13 @@ impl BaseProtocol {
 
 	pub fn generate_request_id(&self) -> String {
 		format!(
-			"{}-{}",
+			"{}-{}-{}",
 			self.get_module_id(),
 			SystemTime::now()
 				.duration_since(UNIX_EPOCH)
 				.expect("Time went backwards. Wtf?")
-				.as_nanos()
+				.as_nanos(),
+			NEXT_REQUEST_NUMBER.fetch_add(1, Ordering::Relaxed)
 		)
 	}
 
@@ -75,10 +82,18 @@ impl BaseProtocol {
 		}
 	}
 
-	pub fn trigger_hook(&self, hook: String) -> BaseMessage {
+	pub fn unregister_hook(&self, hook: String) -> BaseMessage {
+		BaseMessage::UnregisterHookRequest {
+			request_id: self.generate_request_id(),
+			hook,
+		}
+	}
+
+	pub fn trigger_hook(&self, hook: String, data: Value) -> BaseMessage {
 		BaseMessage::TriggerHookRequest {
 			request_id: self.generate_request_id(),
 			hook,
+			data,
 		}
 	}
 
@@ -89,6 +104,13 @@ impl BaseProtocol {
 		}
 	}
 
+	pub fn undeclare_function(&self, function: String) -> BaseMessage {
+		BaseMessage::UndeclareFunctionRequest {
+			request_id: self.generate_request_id(),
+			function,
+		}
+	}
+
 	pub fn call_function(
 		&self,
 		function: String,
@@ -101,16 +123,20 @@ impl BaseProtocol {
 		}
 	}
 
+	pub fn cancel_function_call(&self, request_id: String) -> BaseMessage {
+		BaseMessage::CancelFunctionCallRequest { request_id }
+	}
+
 	pub fn encode(&self, req: BaseMessage) -> Buffer {
 		match self {
-			BaseProtocol::JsonProtocol { .. } => json_protocol::encode(&self, req),
+			BaseProtocol::JsonProtocol { .. } => json_protocol::encode(self, req),
 			_ => panic!("Currently, only JsonProtocol is supported"),
 		}
 	}
 
 	pub fn decode(&self, data: &[u8]) -> BaseMessage {
 		match self {
-			BaseProtocol::JsonProtocol { .. } => json_protocol::decode(&self, data),
+			BaseProtocol::JsonProtocol { .. } => json_protocol::decode(self, data),
 			_ => panic!("Currently, only JsonProtocol is supported"),
 		}
 	}
@@ -1,8 +1,8 @@
 use crate::{
 	connection::Buffer,
-	models::{BaseMessage, Value as GenericValue},
+	models::{BaseMessage, ErrorSource, Value as GenericValue},
 	protocol::base_protocol::BaseProtocol,
-	utils::{request_keys, request_types},
+	utils::{error_sources, request_keys, request_types},
 };
 use serde_json::{from_slice, json, Map, Result, Value};
 use std::collections::HashMap;
@@ -76,11 +76,19 @@ pub fn encode(protocol: &BaseProtocol, req: BaseMessage) -> Buffer {
 					request_keys::TYPE: request_types::REGISTER_HOOK_RESPONSE,
 				}),
 
-				BaseMessage::TriggerHookRequest { request_id, hook } => json!({
-					request_keys::REQUEST_ID: request_id,
-					request_keys::TYPE: request_types::TRIGGER_HOOK_REQUEST,
-					request_keys::HOOK: hook,
-				}),
+				BaseMessage::TriggerHookRequest {
+					request_id,
+					hook,
+					data,
+				} => {
+					let json_data: Value = data.into();
+					json!({
+						request_keys::REQUEST_ID: request_id,
+						request_keys::TYPE: request_types::TRIGGER_HOOK_REQUEST,
+						request_keys::HOOK: hook,
+						request_keys::DATA: json_data,
+					})
+				}
 
 				BaseMessage::TriggerHookResponse { request_id } => json!({
 					request_keys::REQUEST_ID: request_id,
@@ -105,19 +113,104 @@ pub fn encode(protocol: &BaseProtocol, req: BaseMessage) -> Buffer {
 					request_keys::FUNCTION: function,
 				}),
 
+				BaseMessage::CancelFunctionCallRequest { request_id } => json!({
+					request_keys::REQUEST_ID: request_id,
+					request_keys::TYPE: request_types::CANCEL_FUNCTION_CALL_REQUEST,
+				}),
+
+				BaseMessage::FunctionCallStreamChunk { request_id, data } => {
+					let json_data: Value = data.into();
+					json!({
+						request_keys::REQUEST_ID: request_id,
+						request_keys::TYPE: request_types::FUNCTION_CALL_STREAM_CHUNK,
+						request_keys::DATA: json_data,
+					})
+				}
+
+				BaseMessage::FunctionCallStreamEnd { request_id } => json!({
+					request_keys::REQUEST_ID: request_id,
+					request_keys::TYPE: request_types::FUNCTION_CALL_STREAM_END,
+				}),
+
+				BaseMessage::FunctionCallProgress {
+					request_id,
+					progress,
+					message,
+				} => {
+					let mut json_data = json!({
+						request_keys::REQUEST_ID: request_id,
+						request_keys::TYPE: request_types::FUNCTION_CALL_PROGRESS,
+						request_keys::PROGRESS: progress,
+					});
+					if let Some(message) = message {
+						json_data[request_keys::MESSAGE] = Value::String(message);
+					}
+					json_data
+				}
+
+				BaseMessage::UnregisterHookRequest { request_id, hook } => json!({
+					request_keys::REQUEST_ID: request_id,
+					request_keys::TYPE: request_types::UNREGISTER_HOOK_REQUEST,
+					request_keys::HOOK: hook,
+				}),
+
+				BaseMessage::UnregisterHookResponse { request_id } => json!({
+					request_keys::REQUEST_ID: request_id,
+					request_keys::TYPE: request_types::UNREGISTER_HOOK_RESPONSE,
+				}),
+
+				BaseMessage::UndeclareFunctionRequest {
+					request_id,
+					function,
+				} => json!({
+					request_keys::REQUEST_ID: request_id,
+					request_keys::TYPE: request_types::UNDECLARE_FUNCTION_REQUEST,
+					request_keys::FUNCTION: function,
+				}),
+
+				BaseMessage::UndeclareFunctionResponse {
+					request_id,
+					function,
+				} => json!({
+					request_keys::REQUEST_ID: request_id,
+					request_keys::TYPE: request_types::UNDECLARE_FUNCTION_RESPONSE,
+					request_keys::FUNCTION: function,
+				}),
+
 				BaseMessage::Unknown { .. } => json!({
 					request_keys::REQUEST_ID: "undefined",
 					request_keys::TYPE: request_types::ERROR,
 					request_keys::ERROR: 0
 				}),
 
-				BaseMessage::Error { request_id, error } => json!({
-					request_keys::REQUEST_ID: request_id,
-					request_keys::TYPE: request_types::ERROR,
-					request_keys::ERROR: error
-				}),
+				BaseMessage::Error {
+					request_id,
+					error,
+					message,
+					details,
+					source,
+				} => {
+					let source = match source {
+						ErrorSource::Juno => error_sources::JUNO,
+						ErrorSource::Module => error_sources::MODULE,
+					};
+					let mut json_data = json!({
+						request_keys::REQUEST_ID: request_id,
+						request_keys::TYPE: request_types::ERROR,
+						request_keys::ERROR: error,
+						request_keys::SOURCE: source
+					});
+					// Only send the optional fields if they're present,
+					// so that plain error codes look the same as before
+					if let Some(message) = message {
+						json_data[request_keys::MESSAGE] = Value::String(message);
+					}
+					if !details.is_null() {
+						json_data[request_keys::DETAILS] = details.into();
+					}
+					json_data
+				}
 			}
-			.to_string()
 		)
 		.as_bytes()
 		.to_vec(),
@@ -146,7 +239,26 @@ fn decode_internal(data: &[u8]) -> Option<BaseMessage> {
 
 	let r#type = result[request_keys::TYPE].as_u64()?;
 
-	if r#type == 1 {
+	if r#type == 0 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+		let error = result[request_keys::ERROR].as_u64()? as u32;
+		let message = result[request_keys::MESSAGE]
+			.as_str()
+			.map(|message| message.to_string());
+		let details = result[request_keys::DETAILS].clone();
+		let source = match result[request_keys::SOURCE].as_str() {
+			Some(error_sources::MODULE) => ErrorSource::Module,
+			_ => ErrorSource::Juno,
+		};
+
+		Some(BaseMessage::Error {
+			request_id,
+			error,
+			message,
+			details: details.into(),
+			source,
+		})
+	} else if r#type == 1 {
 		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
 		let module_id = result[request_keys::MODULE_ID].as_str()?.to_string();
 		let version = result[request_keys::VERSION].as_str()?.to_string();
@@ -206,8 +318,14 @@ fn decode_internal(data: &[u8]) -> Option<BaseMessage> {
 	} else if r#type == 7 {
 		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
 		let hook = result[request_keys::HOOK].as_str()?.to_string();
+		// Hooks triggered without a payload have null data
+		let data = result[request_keys::DATA].clone();
 
-		Some(BaseMessage::TriggerHookRequest { request_id, hook })
+		Some(BaseMessage::TriggerHookRequest {
+			request_id,
+			hook,
+			data: data.into(),
+		})
 	} else if r#type == 8 {
 		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
 
@@ -228,6 +346,59 @@ fn decode_internal(data: &[u8]) -> Option<BaseMessage> {
 			request_id,
 			function,
 		})
+	} else if r#type == 11 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+
+		Some(BaseMessage::CancelFunctionCallRequest { request_id })
+	} else if r#type == 12 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+		let data = result[request_keys::DATA].clone();
+
+		Some(BaseMessage::FunctionCallStreamChunk {
+			request_id,
+			data: data.into(),
+		})
+	} else if r#type == 13 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+
+		Some(BaseMessage::FunctionCallStreamEnd { request_id })
+	} else if r#type == 14 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+		let progress = result[request_keys::PROGRESS].as_f64()?;
+		let message = result[request_keys::MESSAGE]
+			.as_str()
+			.map(|message| message.to_string());
+
+		Some(BaseMessage::FunctionCallProgress {
+			request_id,
+			progress,
+			message,
+		})
+	} else if r#type == 15 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+		let hook = result[request_keys::HOOK].as_str()?.to_string();
+
+		Some(BaseMessage::UnregisterHookRequest { request_id, hook })
+	} else if r#type == 16 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+
+		Some(BaseMessage::UnregisterHookResponse { request_id })
+	} else if r#type == 17 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+		let function = result[request_keys::FUNCTION].as_str()?.to_string();
+
+		Some(BaseMessage::UndeclareFunctionRequest {
+			request_id,
+			function,
+		})
+	} else if r#type == 18 {
+		let request_id = result[request_keys::REQUEST_ID].as_str()?.to_string();
+		let function = result[request_keys::FUNCTION].as_str()?.to_string();
+
+		Some(BaseMessage::UndeclareFunctionResponse {
+			request_id,
+			function,
+		})
 	} else {
 		Some(BaseMessage::Unknown {
 			request_id: String::default(),
@@ -5,6 +5,10 @@ pub mod request_keys {
 	pub const VERSION: &str = "version";
 	pub const DEPENDENCIES: &str = "dependencies";
 	pub const ERROR: &str = "error";
+	pub const MESSAGE: &str = "message";
+	pub const DETAILS: &str = "details";
+	pub const SOURCE: &str = "source";
+	pub const PROGRESS: &str = "progress";
 	pub const FUNCTION: &str = "function";
 	pub const HOOK: &str = "hook";
 	pub const ARGUMENTS: &str = "arguments";
@@ -22,6 +26,24 @@ pub mod errors {
 	pub const UNKNOWN_FUNCTION: u32 = 5;
 	pub const INVALID_MODULE_ID: u32 = 6;
 	pub const DUPLICATE_MODULE: u32 = 7;
+
+	// Sent by modules rather than juno.
+	// The module is already running as many calls as it's allowed to
+	pub const TOO_MANY_CALLS: u32 = 8;
+	// The function failed on something within its own module
+	pub const INTERNAL_ERROR: u32 = 9;
+	// Something the function waited on timed out
+	pub const TIMED_OUT: u32 = 10;
+	// The function's module is misconfigured
+	pub const INVALID_CONFIGURATION: u32 = 11;
+	// A call the function made in turn failed fast, because that module's circuit was open
+	pub const CIRCUIT_OPEN: u32 = 12;
+}
+
+// Who an error response came from, sent along with module errors as the source
+pub mod error_sources {
+	pub const JUNO: &str = "juno";
+	pub const MODULE: &str = "module";
 }
 
 pub mod request_types {
@@ -41,4 +63,17 @@ pub mod request_types {
 
 	pub const DECLARE_FUNCTION_REQUEST: u64 = 9;
 	pub const DECLARE_FUNCTION_RESPONSE: u64 = 10;
+
+	pub const CANCEL_FUNCTION_CALL_REQUEST: u64 = 11;
+
+	pub const FUNCTION_CALL_STREAM_CHUNK: u64 = 12;
+	pub const FUNCTION_CALL_STREAM_END: u64 = 13;
+
+	pub const FUNCTION_CALL_PROGRESS: u64 = 14;
+
+	pub const UNREGISTER_HOOK_REQUEST: u64 = 15;
+	pub const UNREGISTER_HOOK_RESPONSE: u64 = 16;
+
+	pub const UNDECLARE_FUNCTION_REQUEST: u64 = 17;
+	pub const UNDECLARE_FUNCTION_RESPONSE: u64 = 18;
 }
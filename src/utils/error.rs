@@ -1,9 +1,66 @@
+use crate::{models::Value, utils::errors};
 use std::fmt::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Error {
 	Internal(String),
 	FromJuno(u32),
+	FromModule {
+		code: u32,
+		message: Option<String>,
+		details: Value,
+	},
+	Timeout,
+	Configuration(String),
+	// Juno refused to register the module, with the reason it most likely did so
+	Registration {
+		code: u32,
+		message: String,
+	},
+	// The module's circuit is open, so the call wasn't sent
+	CircuitOpen(String),
+}
+
+impl Error {
+	pub fn from_module(code: u32, message: &str) -> Self {
+		Error::FromModule {
+			code,
+			message: Some(String::from(message)),
+			details: Value::Null,
+		}
+	}
+
+	pub fn with_details(self, details: Value) -> Self {
+		match self {
+			Error::FromModule { code, message, .. } => Error::FromModule {
+				code,
+				message,
+				details,
+			},
+			Error::FromJuno(code) => Error::FromModule {
+				code,
+				message: None,
+				details,
+			},
+			error => Error::FromModule {
+				code: error.code(),
+				message: Some(error.to_string()),
+				details,
+			},
+		}
+	}
+
+	pub fn code(&self) -> u32 {
+		match self {
+			Error::Internal(_) => errors::INTERNAL_ERROR,
+			Error::Timeout => errors::TIMED_OUT,
+			Error::Configuration(_) => errors::INVALID_CONFIGURATION,
+			Error::CircuitOpen(_) => errors::CIRCUIT_OPEN,
+			Error::FromJuno(code) => *code,
+			Error::FromModule { code, .. } => *code,
+			Error::Registration { code, .. } => *code,
+		}
+	}
 }
 
 impl Display for Error {
@@ -11,6 +68,26 @@ impl Display for Error {
 		match self {
 			Error::Internal(string) => write!(f, "Module internal error: {}", string),
 			Error::FromJuno(num) => write!(f, "Juno error code: {}", num),
+			Error::FromModule {
+				code,
+				message: Some(message),
+				..
+			} => write!(f, "Module error code {}: {}", code, message),
+			Error::FromModule { code, .. } => write!(f, "Module error code: {}", code),
+			Error::Timeout => write!(f, "Function call timed out"),
+			Error::Configuration(string) => write!(f, "Invalid module configuration: {}", string),
+			Error::Registration { code, message } => {
+				write!(
+					f,
+					"Juno rejected the registration (code {}): {}",
+					code, message
+				)
+			}
+			Error::CircuitOpen(module_id) => write!(
+				f,
+				"Calls to {} are failing fast until its circuit closes",
+				module_id
+			),
 		}
 	}
 }
@@ -1,5 +1,7 @@
 mod constants;
 mod error;
+mod request_sender;
 
-pub use constants::{errors, request_keys, request_types};
+pub use constants::{error_sources, errors, request_keys, request_types};
 pub use error::{Error, Result};
+pub(crate) use request_sender::{connection_closed, expect_response, response_of, RequestSender};
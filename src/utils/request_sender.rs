@@ -0,0 +1,140 @@
+use crate::{
+	connection::Buffer,
+	functions::{CallGuard, FunctionProgress, PendingCall, PendingRequest},
+	juno_module::ArcRequestList,
+	models::{BaseMessage, Value},
+	protocol::BaseProtocol,
+	utils::{Error, Result},
+};
+use futures::channel::{
+	mpsc::{unbounded, UnboundedSender},
+	oneshot::{channel, Receiver},
+};
+use std::{collections::HashMap, sync::Arc};
+
+// Sends requests to juno and waits for their responses, without borrowing the module.
+// Used by everything that outlives the call that created it
+#[derive(Clone)]
+pub struct RequestSender {
+	requests: ArcRequestList,
+	write_sender: UnboundedSender<Buffer>,
+	protocol: Arc<BaseProtocol>,
+}
+
+impl RequestSender {
+	pub fn new(
+		requests: ArcRequestList,
+		write_sender: UnboundedSender<Buffer>,
+		protocol: Arc<BaseProtocol>,
+	) -> Self {
+		RequestSender {
+			requests,
+			write_sender,
+			protocol,
+		}
+	}
+
+	pub fn get_protocol(&self) -> &BaseProtocol {
+		&self.protocol
+	}
+
+	pub async fn send_request(&self, request: BaseMessage) -> Result<Value> {
+		let response = self.start_request(request).await?;
+		response_of(response).await
+	}
+
+	// Sends the request without waiting for juno to respond to it.
+	// Fails right away if the connection is closed
+	pub async fn start_request(&self, request: BaseMessage) -> Result<Receiver<Result<Value>>> {
+		let request_id = request.get_request_id().clone();
+		let response = expect_response(&self.requests, request_id.clone()).await;
+		if self
+			.write_sender
+			.unbounded_send(self.protocol.encode(request))
+			.is_err()
+		{
+			// Nothing is ever going to respond to it
+			self.requests.lock().await.remove(&request_id);
+			return Err(connection_closed());
+		}
+		Ok(response)
+	}
+
+	pub async fn start_function_call(
+		&self,
+		fn_name: String,
+		args: HashMap<String, Value>,
+	) -> Result<PendingCall> {
+		let (sender, receiver) = channel::<Result<Value>>();
+		let (progress_sender, progress_receiver) = unbounded::<FunctionProgress>();
+		let guard = self
+			.send_function_call(
+				fn_name,
+				args,
+				PendingRequest::function_call(sender, progress_sender),
+			)
+			.await?;
+		Ok(PendingCall::new(guard, receiver, progress_receiver))
+	}
+
+	pub async fn send_function_call(
+		&self,
+		fn_name: String,
+		args: HashMap<String, Value>,
+		pending_request: PendingRequest,
+	) -> Result<CallGuard> {
+		let request = self.protocol.call_function(fn_name, args);
+		let request_id = request.get_request_id().clone();
+		let cancel_request = self
+			.protocol
+			.encode(self.protocol.cancel_function_call(request_id.clone()));
+
+		self.requests
+			.lock()
+			.await
+			.insert(request_id.clone(), pending_request);
+		if self
+			.write_sender
+			.unbounded_send(self.protocol.encode(request))
+			.is_err()
+		{
+			self.requests.lock().await.remove(&request_id);
+			return Err(connection_closed());
+		}
+
+		Ok(CallGuard::new(
+			request_id,
+			self.requests.clone(),
+			self.write_sender.clone(),
+			cancel_request,
+		))
+	}
+}
+
+// Listens for the response to a request. Call it before sending the request,
+// so that the response can't be missed
+pub async fn expect_response(
+	requests: &ArcRequestList,
+	request_id: String,
+) -> Receiver<Result<Value>> {
+	let (sender, receiver) = channel::<Result<Value>>();
+	requests
+		.lock()
+		.await
+		.insert(request_id, PendingRequest::single(sender));
+	receiver
+}
+
+pub async fn response_of(receiver: Receiver<Result<Value>>) -> Result<Value> {
+	match receiver.await {
+		Ok(value) => value,
+		Err(_) => Err(Error::Internal(String::from(
+			"Request sender was dropped before data could be retrieved",
+		))),
+	}
+}
+
+// What requests fail with once the connection to juno is gone
+pub fn connection_closed() -> Error {
+	Error::Internal(String::from("Connection closed"))
+}
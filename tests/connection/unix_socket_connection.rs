@@ -24,6 +24,7 @@ async fn should_connect_async() -> Result<()> {
 
 	let (..) = future::join(connection_listener, connection.setup_connection()).await;
 
+	drop(incoming);
 	drop(socket);
 	remove_file("./temp-1.sock").await?;
 
@@ -56,6 +57,7 @@ async fn should_connect_and_send_data_async() -> Result<()> {
 
 	assert_eq!(read_buffer.to_vec(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
 
+	drop(incoming);
 	drop(socket);
 	remove_file("./temp-2.sock").await?;
 
@@ -90,6 +92,7 @@ async fn should_connect_and_read_data_async() -> Result<()> {
 
 	assert_eq!(read_buffer, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
 
+	drop(incoming);
 	drop(socket);
 	remove_file("./temp-3.sock").await?;
 
@@ -125,6 +128,7 @@ async fn should_connect_and_send_data_from_cloned_sender_async() -> Result<()> {
 
 	assert_eq!(read_buffer.to_vec(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
 
+	drop(incoming);
 	drop(socket);
 	remove_file("./temp-4.sock").await?;
 
@@ -182,6 +186,7 @@ async fn should_setup_connection_twice_and_panic_async() -> Result<()> {
 	result.unwrap();
 	let (_, result) = future::join(incoming.next(), connection.setup_connection()).await;
 	result.unwrap();
+	drop(incoming);
 	drop(socket);
 
 	Ok(())
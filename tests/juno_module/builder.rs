@@ -0,0 +1,192 @@
+use async_std::{fs::remove_file, io::BufReader, os::unix::net::UnixListener, prelude::*, task};
+use futures::{channel::mpsc::unbounded, future};
+use juno::{
+	json,
+	models::{Dependency, Version},
+	ConnectRetryPolicy, Error, JunoModule,
+};
+use std::{collections::HashMap, time::Duration};
+
+use super::support::{accept_registration, connect_module, setup_module, FakeRouter};
+
+#[test]
+fn should_reject_invalid_builder_configuration() {
+	task::block_on(async {
+		let result = JunoModule::builder()
+			.address("./temp-module-25.sock")
+			.version("1.0.0")
+			.build()
+			.await;
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		let result = JunoModule::builder()
+			.address("./temp-module-25.sock")
+			.module_id("test.module")
+			.version("1.0.0")
+			.build()
+			.await;
+		assert!(matches!(result, Err(Error::Configuration(_))));
+	});
+}
+
+#[test]
+fn should_build_module_once_juno_is_reachable() {
+	task::block_on(async {
+		let socket_path = "./temp-module-26.sock";
+		let _ = remove_file(socket_path).await;
+
+		let router = task::spawn(async move {
+			// Juno only comes up after the module started connecting
+			task::sleep(Duration::from_millis(50)).await;
+			let listener = UnixListener::bind(socket_path).await.unwrap();
+			let (stream, _) = listener.accept().await.unwrap();
+			let mut router = FakeRouter {
+				stream: stream.clone(),
+				lines: BufReader::new(stream).lines(),
+			};
+			let registration = router.respond(2).await;
+			router.accept_discovery().await;
+			remove_file(socket_path).await.unwrap();
+			(router, registration)
+		});
+		let (error_sender, mut hook_errors) = unbounded();
+		let mut module = JunoModule::builder()
+			.unix_socket(socket_path)
+			.module_id("test")
+			.version("1.0.0")
+			.dependency("other", "^1.0.0")
+			.connect_retry_policy(ConnectRetryPolicy::fixed(20, Duration::from_millis(20)))
+			.call_timeout(Duration::from_millis(20))
+			.hook_error_sink(move |hook, _| {
+				error_sender.unbounded_send(hook.to_string()).unwrap();
+			})
+			.build()
+			.await
+			.unwrap();
+		let (mut router, registration) = router.await;
+		assert_eq!(registration["moduleId"], "test");
+		assert_eq!(registration["dependencies"]["other"], "^1.0.0");
+
+		let (result, _) = future::join(
+			module.call_function("other.function", HashMap::new()),
+			router.read_message(),
+		)
+		.await;
+		assert!(matches!(result, Err(Error::Timeout)));
+		let cancel = router.read_message().await;
+		assert_eq!(cancel["type"], 11);
+
+		let (registered, _) = future::join(
+			module.register_async_hook("orders.created", |_| async {
+				Err(Error::Internal(String::from("Broken")))
+			}),
+			router.respond(6),
+		)
+		.await;
+		registered.unwrap();
+		router
+			.write_message(json!({
+				"requestId": "trigger-1",
+				"type": 7,
+				"hook": "orders.created",
+			}))
+			.await;
+		assert_eq!(hook_errors.next().await.unwrap(), "orders.created");
+	});
+}
+
+#[test]
+fn should_configure_module_from_file_and_environment() {
+	task::block_on(async {
+		let socket_path = "./temp-module-27.sock";
+		let config_path = "./temp-module-27.toml";
+		async_std::fs::write(
+			config_path,
+			format!(
+				"address = \"{}\"\nmodule_id = \"from-file\"\nversion = \"1.0.0\"\n\n[dependencies]\nother = \"^1.0.0\"\n",
+				socket_path
+			),
+		)
+		.await
+		.unwrap();
+		// The environment takes precedence over the file
+		let mut vars = HashMap::new();
+		vars.insert(String::from("JUNO_VERSION"), String::from("2.0.0"));
+		let builder = JunoModule::from_config_with_vars(config_path, &vars);
+		remove_file(config_path).await.unwrap();
+
+		let (module, _, registration) =
+			connect_module(socket_path, builder.unwrap().build(), accept_registration).await;
+		module.unwrap();
+		assert_eq!(registration["moduleId"], "from-file");
+		assert_eq!(registration["version"], "2.0.0");
+		assert_eq!(registration["dependencies"]["other"], "^1.0.0");
+
+		async_std::fs::write("./temp-module-27.json", "{ \"unknown\": 1 }")
+			.await
+			.unwrap();
+		let result = JunoModule::from_config("./temp-module-27.json");
+		remove_file("./temp-module-27.json").await.unwrap();
+		assert!(matches!(result, Err(Error::Configuration(_))));
+	});
+}
+
+#[test]
+fn should_validate_versions_and_explain_rejected_registrations() {
+	task::block_on(async {
+		assert!(Dependency::new("other", "^1.x.0").is_err());
+		let dependency = Dependency::new("other", "^1.2").unwrap();
+		assert!(dependency.matches(&Version::new(1, 4, 0)));
+		assert!(!dependency.matches(&Version::new(2, 0, 0)));
+
+		let result = JunoModule::builder()
+			.address("./temp-module-28.sock")
+			.module_id("test")
+			.version("1.0")
+			.build()
+			.await;
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		let result = JunoModule::builder()
+			.address("./temp-module-28.sock")
+			.module_id("test")
+			.version("1.0.0")
+			.dependency("other", "^1.x.0")
+			.build()
+			.await;
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		let socket_path = "./temp-module-28.sock";
+		let module = JunoModule::builder()
+			.unix_socket(socket_path)
+			.module_id("test")
+			.version("1.0.0")
+			.require(dependency)
+			.build();
+		let (result, _, _) = connect_module(socket_path, module, |registration| {
+			json!({
+				"type": 0,
+				"requestId": registration["requestId"],
+				"error": 0,
+			})
+		})
+		.await;
+		match result {
+			Err(Error::Registration { code, message }) => {
+				assert_eq!(code, 0);
+				assert!(message.contains("other ^1.2"));
+			}
+			_ => panic!("Registration should have been rejected"),
+		}
+	});
+}
+
+#[test]
+fn should_refuse_to_initialize_a_module_twice() {
+	task::block_on(async {
+		let (mut module, _router) = setup_module("./temp-module-40.sock").await;
+
+		let result = module.initialize("test", "1.0.0", HashMap::new()).await;
+		assert!(matches!(result, Err(Error::Internal(_))));
+	});
+}
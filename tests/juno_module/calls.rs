@@ -0,0 +1,568 @@
+use async_std::{prelude::*, task};
+use futures::{channel::mpsc::unbounded, future};
+use juno::{
+	errors,
+	functions::{FunctionProgress, RetryPolicy},
+	json,
+	models::Value,
+	Error, JunoModule,
+};
+use std::{collections::HashMap, time::Duration};
+
+use super::support::{accept_registration, connect_module, setup_module, setup_module_with};
+
+#[test]
+fn should_send_cancel_request_when_pending_call_is_dropped() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-1.sock").await;
+
+		let pending_call = module
+			.start_function_call("other.function", HashMap::new())
+			.await
+			.unwrap();
+		let request = router.read_message().await;
+		assert_eq!(request["type"], 3);
+		assert_eq!(
+			&request["requestId"],
+			pending_call.get_request_id().as_str()
+		);
+
+		pending_call.cancel();
+		let cancel = router.read_message().await;
+		assert_eq!(cancel["type"], 11);
+		assert_eq!(cancel["requestId"], request["requestId"]);
+	});
+}
+
+#[test]
+fn should_collect_stream_chunks_for_call_function() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-5.sock").await;
+
+		let pending_call = module
+			.start_function_call("other.count", HashMap::new())
+			.await
+			.unwrap();
+		let request = router.read_message().await;
+		for message in [
+			json!({ "requestId": request["requestId"], "type": 12, "data": 1 }),
+			json!({ "requestId": request["requestId"], "type": 12, "data": 2 }),
+			json!({ "requestId": request["requestId"], "type": 13 }),
+		] {
+			router.write_message(message).await;
+		}
+
+		assert_eq!(pending_call.await.unwrap(), json!([1, 2]).into());
+	});
+}
+
+#[test]
+fn should_reset_timeout_on_progress() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-7.sock").await;
+
+		let (progress_sender, mut progress_receiver) = unbounded::<FunctionProgress>();
+		let pending_call = module
+			.start_function_call("other.work", HashMap::new())
+			.await
+			.unwrap()
+			.with_timeout(Duration::from_millis(200))
+			.on_progress(move |progress| progress_sender.unbounded_send(progress).unwrap());
+		let request = router.read_message().await;
+
+		let router = async move {
+			for progress in [25.0, 50.0, 75.0] {
+				task::sleep(Duration::from_millis(100)).await;
+				router
+					.write_message(json!({
+						"requestId": request["requestId"],
+						"type": 14,
+						"progress": progress,
+					}))
+					.await;
+			}
+			task::sleep(Duration::from_millis(100)).await;
+			router
+				.write_message(json!({
+					"requestId": request["requestId"],
+					"type": 4,
+					"data": "done",
+				}))
+				.await;
+		};
+		let (result, _) = future::join(pending_call, router).await;
+
+		assert_eq!(result.unwrap(), json!("done").into());
+		let progress = progress_receiver.next().await.unwrap();
+		assert_eq!(progress.progress, 25.0);
+		assert_eq!(progress.message, None);
+	});
+}
+
+#[test]
+fn should_time_out_and_cancel_call() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-8.sock").await;
+
+		let result = module
+			.start_function_call("other.work", HashMap::new())
+			.await
+			.unwrap()
+			.with_timeout(Duration::from_millis(10))
+			.await;
+		assert!(matches!(result, Err(Error::Timeout)));
+
+		let request = router.read_message().await;
+		let cancel = router.read_message().await;
+		assert_eq!(cancel["type"], 11);
+		assert_eq!(cancel["requestId"], request["requestId"]);
+	});
+}
+
+#[test]
+fn should_only_send_delayed_calls_that_werent_cancelled() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-20.sock").await;
+
+		module
+			.call_function_after(Duration::from_millis(20), "other.cancelled", HashMap::new())
+			.unwrap()
+			.cancel();
+		let delayed_call = module
+			.call_function_at(
+				std::time::Instant::now() + Duration::from_millis(40),
+				"other.delayed",
+				HashMap::new(),
+			)
+			.unwrap();
+
+		let request = router.read_message().await;
+		assert_eq!(request["type"], 3);
+		assert_eq!(request["function"], "other.delayed");
+		router
+			.write_message(json!({
+				"requestId": request["requestId"],
+				"type": 4,
+				"data": "done",
+			}))
+			.await;
+		assert_eq!(delayed_call.await.unwrap(), json!("done").into());
+	});
+}
+
+#[test]
+fn should_call_many_functions_at_once() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-32.sock").await;
+
+		// Every call is sent before any of them is responded to, and they're responded to in reverse
+		let responses = async {
+			let mut requests = vec![];
+			for _ in 0..3 {
+				requests.push(router.read_message().await);
+			}
+			for request in requests.iter().rev() {
+				let response = if request["function"] == "other.fail" {
+					json!({ "type": 0, "requestId": request["requestId"], "error": 5 })
+				} else {
+					json!({ "type": 4, "requestId": request["requestId"], "data": request["function"] })
+				};
+				router.write_message(response).await;
+			}
+		};
+		let calls = vec![
+			("other.first", HashMap::new()),
+			("other.fail", HashMap::new()),
+			("other.second", HashMap::new()),
+		];
+		let (_, results) = future::join(responses, module.call_many(calls)).await;
+		assert_eq!(results.len(), 3);
+		assert_eq!(
+			results[0].as_ref().unwrap(),
+			&Value::String(String::from("other.first"))
+		);
+		assert!(matches!(results[1], Err(Error::FromJuno(5))));
+		assert_eq!(
+			results[2].as_ref().unwrap(),
+			&Value::String(String::from("other.second"))
+		);
+
+		let responses = async {
+			let first = router.read_message().await;
+			let second = router.read_message().await;
+			router
+				.write_message(json!({ "type": 0, "requestId": second["requestId"], "error": 5 }))
+				.await;
+			// The call that was still running is cancelled
+			(first, router.read_message().await)
+		};
+		let calls = vec![
+			("other.first", HashMap::new()),
+			("other.fail", HashMap::new()),
+		];
+		let ((first, cancel), result) = future::join(responses, module.try_call_many(calls)).await;
+		assert!(matches!(result, Err(Error::FromJuno(5))));
+		assert_eq!(cancel["requestId"], first["requestId"]);
+	});
+}
+
+#[test]
+fn should_broadcast_call_to_modules_declaring_function() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-33.sock").await;
+
+		// Nobody has announced themselves yet
+		let result = module
+			.broadcast_call("flush", HashMap::new(), Duration::from_millis(100))
+			.await;
+		assert!(matches!(result, Err(Error::Internal(_))));
+
+		let announce = |module_id: &str, is_answer: bool| {
+			json!({
+				"requestId": format!("{}-announcement", module_id),
+				"type": 7,
+				"hook": "__juno.modules",
+				"data": { "moduleId": module_id, "isAnswer": is_answer },
+			})
+		};
+		// Answers, and the module's own announcement, aren't answered
+		for (module_id, is_answer) in [("payments", true), ("slow", true), ("test", false)] {
+			router.write_message(announce(module_id, is_answer)).await;
+		}
+		// A module that just registered is answered, so that it learns of this one
+		for module_id in ["cache", "orders", "search"] {
+			router.write_message(announce(module_id, false)).await;
+			let answer = router.respond(8).await;
+			assert_eq!(answer["hook"], "__juno.modules");
+			assert_eq!(answer["data"]["moduleId"], "test");
+			assert_eq!(answer["data"]["isAnswer"], true);
+		}
+
+		let responses = async {
+			let mut functions = vec![];
+			for _ in 0..5 {
+				let request = router.read_message().await;
+				let function = request["function"].as_str().unwrap().to_string();
+				let response = match function.as_str() {
+					"cache.flush" => {
+						json!({ "type": 4, "requestId": request["requestId"], "data": true })
+					}
+					"orders.flush" => json!({
+						"type": 0,
+						"requestId": request["requestId"],
+						"error": errors::UNKNOWN_FUNCTION,
+					}),
+					"payments.flush" => json!({
+						"type": 0,
+						"requestId": request["requestId"],
+						"error": 1,
+						"message": "Failed",
+						"source": "module",
+					}),
+					// Has disconnected since it announced itself
+					"search.flush" => json!({
+						"type": 0,
+						"requestId": request["requestId"],
+						"error": errors::UNKNOWN_MODULE,
+					}),
+					// Never answers in time
+					_ => json!(null),
+				};
+				if !response.is_null() {
+					router.write_message(response).await;
+				}
+				functions.push(function);
+			}
+			functions
+		};
+		let (mut functions, results) = future::join(
+			responses,
+			module.broadcast_call("flush", HashMap::new(), Duration::from_millis(100)),
+		)
+		.await;
+		functions.sort();
+		assert_eq!(
+			functions,
+			vec![
+				"cache.flush",
+				"orders.flush",
+				"payments.flush",
+				"search.flush",
+				"slow.flush"
+			]
+		);
+
+		let results = results.unwrap();
+		assert_eq!(results.len(), 2);
+		assert_eq!(results[0].0, "cache");
+		assert_eq!(results[0].1.as_ref().unwrap(), &Value::Bool(true));
+		assert_eq!(results[1].0, "payments");
+		assert!(matches!(
+			results[1].1,
+			Err(Error::FromModule { code: 1, .. })
+		));
+	});
+}
+
+#[test]
+fn should_broadcast_call_to_dependencies() {
+	task::block_on(async {
+		let mut dependencies = HashMap::new();
+		dependencies.insert(String::from("cache"), String::from("^1.0.0"));
+		let socket_path = "./temp-module-46.sock";
+		let (module, mut router) = setup_module_with(
+			JunoModule::from_unix_socket(socket_path),
+			socket_path,
+			dependencies,
+		)
+		.await;
+
+		let response = async {
+			let request = router.read_message().await;
+			router
+				.write_message(
+					json!({ "type": 4, "requestId": request["requestId"], "data": true }),
+				)
+				.await;
+			request
+		};
+		let (request, results) = future::join(
+			response,
+			module.broadcast_call("flush", HashMap::new(), Duration::from_millis(100)),
+		)
+		.await;
+		assert_eq!(request["function"], "cache.flush");
+		let results = results.unwrap();
+		assert_eq!(results.len(), 1);
+		assert_eq!(results[0].0, "cache");
+	});
+}
+
+// Fails with whichever error its argument names
+fn fail_with(args: HashMap<String, Value>) -> juno::Result<Value> {
+	let variant = match args.get("variant") {
+		Some(Value::String(variant)) => variant.clone(),
+		_ => String::new(),
+	};
+	Err(match variant.as_str() {
+		"internal" => Error::Internal(String::from("Broken")),
+		"juno" => Error::FromJuno(errors::UNKNOWN_FUNCTION),
+		"module" => Error::FromModule {
+			code: 7,
+			message: None,
+			details: Value::Null,
+		},
+		"module with message" => Error::from_module(7, "Failed").with_details(Value::Bool(true)),
+		"timeout" => Error::Timeout,
+		"configuration" => Error::Configuration(String::from("Broken")),
+		"registration" => Error::Registration {
+			code: errors::DUPLICATE_MODULE,
+			message: String::from("Taken"),
+		},
+		_ => Error::CircuitOpen(String::from("other")),
+	})
+}
+
+#[test]
+fn should_pass_every_kind_of_error_through_a_call() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-34.sock").await;
+		let (declared, _) = future::join(
+			module.declare_fallible_function("fail", fail_with),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		for variant in [
+			"internal",
+			"juno",
+			"module",
+			"module with message",
+			"timeout",
+			"configuration",
+			"registration",
+			"circuit open",
+		] {
+			let mut args = HashMap::new();
+			args.insert(String::from("variant"), Value::String(variant.to_string()));
+			// The module calls itself, and juno passes the call and the error along
+			let relay = async {
+				let call = router.read_message().await;
+				router
+					.write_message(json!({
+						"requestId": "caller-1",
+						"type": 3,
+						"function": "fail",
+						"arguments": call["arguments"],
+					}))
+					.await;
+				let mut response = router.read_message().await;
+				response["requestId"] = call["requestId"].clone();
+				router.write_message(response.clone()).await;
+				response
+			};
+			let (response, result) =
+				future::join(relay, module.call_function("test.fail", args)).await;
+			let error = result.unwrap_err();
+
+			match variant {
+				"juno" => {
+					assert_eq!(response["source"], "juno");
+					assert!(matches!(error, Error::FromJuno(errors::UNKNOWN_FUNCTION)));
+					continue;
+				}
+				"module" => {
+					assert!(response["message"].is_null());
+					assert!(matches!(
+						error,
+						Error::FromModule {
+							code: 7,
+							message: None,
+							details: Value::Null,
+						}
+					));
+					continue;
+				}
+				"module with message" => {
+					assert!(matches!(
+						error,
+						Error::FromModule {
+							code: 7,
+							message: Some(message),
+							details: Value::Bool(true),
+						} if message == "Failed"
+					));
+					continue;
+				}
+				_ => {}
+			}
+			// Errors that only mean something within the called module come back as its own
+			let (code, message) = match error {
+				Error::FromModule {
+					code,
+					message: Some(message),
+					..
+				} => (code, message),
+				error => panic!("{} came back as {:?}", variant, error),
+			};
+			assert_eq!(response["source"], "module");
+			let expected_code = match variant {
+				"internal" => errors::INTERNAL_ERROR,
+				"timeout" => errors::TIMED_OUT,
+				"configuration" => errors::INVALID_CONFIGURATION,
+				"registration" => errors::DUPLICATE_MODULE,
+				_ => errors::CIRCUIT_OPEN,
+			};
+			assert_eq!(code, expected_code, "{}", variant);
+			assert!(!message.is_empty());
+		}
+	});
+}
+
+#[test]
+fn should_retry_delayed_calls_like_any_other_call() {
+	task::block_on(async {
+		let socket_path = "./temp-module-38.sock";
+		let module = JunoModule::builder()
+			.unix_socket(socket_path)
+			.module_id("test")
+			.version("1.0.0")
+			.retry_policy(RetryPolicy::exponential(2, Duration::from_millis(10)))
+			.build();
+		let (module, mut router, _) =
+			connect_module(socket_path, module, accept_registration).await;
+		let module = module.unwrap();
+
+		let delayed_call = module
+			.call_function_after(Duration::from_millis(10), "other.function", HashMap::new())
+			.unwrap();
+		let responses = async {
+			let first = router.read_message().await;
+			router
+				.write_message(json!({
+					"type": 0,
+					"requestId": first["requestId"],
+					"error": 4,
+				}))
+				.await;
+			let second = router.read_message().await;
+			router
+				.write_message(json!({
+					"type": 4,
+					"requestId": second["requestId"],
+					"data": "done",
+				}))
+				.await;
+		};
+		let (_, result) = future::join(responses, delayed_call).await;
+		assert_eq!(result.unwrap(), Value::String(String::from("done")));
+	});
+}
+
+#[test]
+fn should_give_every_call_made_at_once_its_own_request_id() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-42.sock").await;
+
+		let count = 200;
+		let responses = async {
+			let mut request_ids = std::collections::HashSet::new();
+			for _ in 0..count {
+				let request = router.read_message().await;
+				request_ids.insert(request["requestId"].as_str().unwrap().to_string());
+				router
+					.write_message(json!({
+						"type": 4,
+						"requestId": request["requestId"],
+						"data": request["arguments"]["index"],
+					}))
+					.await;
+			}
+			request_ids.len()
+		};
+		let calls = (0..count)
+			.map(|index| {
+				let mut args = HashMap::new();
+				args.insert(String::from("index"), Value::String(index.to_string()));
+				("other.echo", args)
+			})
+			.collect();
+		let (unique_ids, results) = future::join(responses, module.call_many(calls)).await;
+		assert_eq!(unique_ids, count);
+		for (index, result) in results.into_iter().enumerate() {
+			assert_eq!(result.unwrap(), Value::String(index.to_string()));
+		}
+	});
+}
+
+#[test]
+fn should_fail_and_retry_calls_lost_to_a_disconnect() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-43.sock").await;
+		let policy = RetryPolicy {
+			jitter: false,
+			..RetryPolicy::exponential(3, Duration::from_millis(30))
+		};
+
+		// Juno goes away while the call is in flight
+		let disconnect = async move {
+			let request = router.read_message().await;
+			assert_eq!(request["function"], "other.function");
+			drop(router);
+		};
+		let started_at = std::time::Instant::now();
+		let (_, result) = future::join(
+			disconnect,
+			module.call_function_with_retry("other.function", HashMap::new(), &policy),
+		)
+		.await;
+		assert!(matches!(result, Err(Error::Internal(_))));
+		// Both retries waited out their backoff before failing as well
+		assert!(started_at.elapsed() >= Duration::from_millis(90));
+
+		// Anything sent afterwards fails right away
+		let result = module.call_function("other.function", HashMap::new()).await;
+		assert!(matches!(result, Err(Error::Internal(_))));
+	});
+}
@@ -0,0 +1,528 @@
+use async_std::{prelude::*, task};
+use futures::{channel::mpsc::unbounded, future, stream};
+use juno::{
+	errors,
+	functions::{FunctionHandler, JunoService, ServiceFunctions},
+	hooks::HookExecution,
+	json,
+	models::Value,
+	Error, JunoModule,
+};
+use std::{
+	collections::HashMap,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+use super::support::{setup_module, setup_module_with, OrderCreated};
+
+#[test]
+fn should_signal_cancellation_to_async_function() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-2.sock").await;
+
+		let (cancelled_sender, mut cancelled_receiver) = unbounded::<bool>();
+		let (declared, _) = future::join(
+			module.declare_async_function("wait", move |context, _| {
+				let cancelled_sender = cancelled_sender.clone();
+				async move {
+					context.cancelled().await;
+					cancelled_sender
+						.unbounded_send(context.is_cancelled())
+						.unwrap();
+					Ok(Value::Null)
+				}
+			}),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 3,
+				"function": "wait",
+				"arguments": {},
+			}))
+			.await;
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 11,
+			}))
+			.await;
+		assert!(cancelled_receiver.next().await.unwrap());
+
+		router
+			.write_message(json!({
+				"requestId": "caller-2",
+				"type": 3,
+				"function": "unknown",
+				"arguments": {},
+			}))
+			.await;
+
+		// The cancelled call never responds, so the next message is the unknown function error
+		let response = router.read_message().await;
+		assert_eq!(response["requestId"], "caller-2");
+		assert_eq!(response["type"], 0);
+		assert_eq!(response["error"], 5);
+	});
+}
+
+#[test]
+fn should_send_stream_function_response_in_chunks() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-3.sock").await;
+
+		let (declared, _) = future::join(
+			module.declare_stream_function("count", |_, _| {
+				stream::iter(vec![json!(1).into(), json!(2).into()])
+			}),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 3,
+				"function": "count",
+				"arguments": {},
+			}))
+			.await;
+
+		let first = router.read_message().await;
+		assert_eq!(first["type"], 12);
+		assert_eq!(first["data"], 1);
+		let second = router.read_message().await;
+		assert_eq!(second["type"], 12);
+		assert_eq!(second["data"], 2);
+		let end = router.read_message().await;
+		assert_eq!(end["type"], 13);
+		assert_eq!(end["requestId"], "caller-1");
+	});
+}
+
+#[test]
+fn should_receive_function_call_stream() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-4.sock").await;
+
+		let mut stream = module
+			.call_function_stream("other.count", HashMap::new())
+			.await
+			.unwrap();
+		let request = router.read_message().await;
+		for data in 1..3 {
+			router
+				.write_message(json!({
+					"requestId": request["requestId"],
+					"type": 12,
+					"data": data,
+				}))
+				.await;
+		}
+		router
+			.write_message(json!({
+				"requestId": request["requestId"],
+				"type": 13,
+			}))
+			.await;
+
+		assert_eq!(stream.next().await.unwrap().unwrap(), json!(1).into());
+		assert_eq!(stream.next().await.unwrap().unwrap(), json!(2).into());
+		assert!(stream.next().await.is_none());
+	});
+}
+
+#[test]
+fn should_send_progress_from_async_function() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-6.sock").await;
+
+		let (declared, _) = future::join(
+			module.declare_async_function("work", |context, _| async move {
+				context.report_progress(50.0, Some("Halfway there"));
+				Ok(Value::Null)
+			}),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 3,
+				"function": "work",
+				"arguments": {},
+			}))
+			.await;
+
+		let progress = router.read_message().await;
+		assert_eq!(progress["type"], 14);
+		assert_eq!(progress["requestId"], "caller-1");
+		assert_eq!(progress["progress"], 50.0);
+		assert_eq!(progress["message"], "Halfway there");
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 4);
+	});
+}
+
+#[test]
+fn should_reject_calls_to_undeclared_function() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-12.sock").await;
+
+		let (declared, _) = future::join(
+			module.declare_function("work", |_| Value::Null),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+		let (undeclared, undeclare) =
+			future::join(module.undeclare_function("work"), router.respond(18)).await;
+		undeclared.unwrap();
+		assert_eq!(undeclare["type"], 17);
+		assert_eq!(undeclare["function"], "work");
+
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 3,
+				"function": "work",
+				"arguments": {},
+			}))
+			.await;
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 0);
+		assert_eq!(response["error"], 5);
+	});
+}
+
+#[test]
+fn should_reject_duplicate_function_declaration() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-13.sock").await;
+
+		let (declared, _) = future::join(
+			module.declare_function("work", |_| Value::Null),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		let result = module.declare_function("work", |_| Value::Null).await;
+		assert!(matches!(result, Err(Error::Internal(_))));
+
+		// A declaration juno rejected can be made again
+		let rejection = async {
+			let request = router.read_message().await;
+			router
+				.write_message(json!({
+					"type": 0,
+					"requestId": request["requestId"],
+					"error": errors::MALFORMED_REQUEST,
+				}))
+				.await;
+		};
+		let (_, result) =
+			future::join(rejection, module.declare_function("other", |_| Value::Null)).await;
+		assert!(matches!(result, Err(Error::FromJuno(_))));
+		let (declared, _) = future::join(
+			module.declare_function("other", |_| Value::Null),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+	});
+}
+
+#[test]
+fn should_call_replaced_function_implementation() {
+	task::block_on(async {
+		let mut module = JunoModule::from_unix_socket("./temp-module-14.sock");
+		let result = module
+			.replace_function("version", FunctionHandler::Infallible(|_| Value::Null))
+			.await;
+		assert!(matches!(result, Err(Error::Internal(_))));
+		let (mut module, mut router) =
+			setup_module_with(module, "./temp-module-14.sock", HashMap::new()).await;
+
+		let (declared, _) = future::join(
+			module.declare_function("version", |_| json!(1).into()),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+		module
+			.replace_function(
+				"version",
+				FunctionHandler::from_async(|_, _| async { Ok(json!(2).into()) }),
+			)
+			.await
+			.unwrap();
+
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 3,
+				"function": "version",
+				"arguments": {},
+			}))
+			.await;
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 4);
+		assert_eq!(response["data"], 2);
+	});
+}
+
+#[test]
+fn should_hand_incoming_calls_over_to_a_stream() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-21.sock").await;
+
+		let (calls, _) = future::join(module.incoming_calls("work"), router.respond(10)).await;
+		let mut calls = calls.unwrap();
+		task::spawn(async move {
+			while let Some(call) = calls.next().await {
+				match call.get_arguments().get("value").cloned() {
+					Some(value) => call.respond(value).await,
+					None => call.fail(Error::from_module(5, "No value")).await,
+				}
+			}
+		});
+
+		router
+			.write_message(json!({
+				"requestId": "caller-1",
+				"type": 3,
+				"function": "work",
+				"arguments": { "value": 42 },
+			}))
+			.await;
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 4);
+		assert_eq!(response["requestId"], "caller-1");
+		assert_eq!(response["data"], 42);
+
+		router
+			.write_message(json!({
+				"requestId": "caller-2",
+				"type": 3,
+				"function": "work",
+				"arguments": {},
+			}))
+			.await;
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 0);
+		assert_eq!(response["requestId"], "caller-2");
+		assert_eq!(response["error"], 5);
+	});
+}
+
+#[test]
+fn should_answer_deferred_calls_later_or_time_out() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-22.sock").await;
+
+		let (responder_sender, mut responders) = unbounded();
+		let (declared, _) = future::join(
+			module.declare_deferred_function(
+				"approve",
+				Duration::from_millis(50),
+				move |_, _, responder| {
+					responder_sender.unbounded_send(responder).unwrap();
+				},
+			),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		for request_id in ["caller-1", "caller-2"] {
+			router
+				.write_message(json!({
+					"requestId": request_id,
+					"type": 3,
+					"function": "approve",
+					"arguments": {},
+				}))
+				.await;
+		}
+		let approved = responders.next().await.unwrap();
+		assert_eq!(approved.get_request_id(), "caller-1");
+		let _ignored = responders.next().await.unwrap();
+		task::spawn(approved.respond(json!("approved").into()));
+
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 4);
+		assert_eq!(response["requestId"], "caller-1");
+		assert_eq!(response["data"], "approved");
+
+		let response = router.read_message().await;
+		assert_eq!(response["type"], 0);
+		assert_eq!(response["requestId"], "caller-2");
+		assert_eq!(response["message"], Error::Timeout.to_string());
+	});
+}
+
+struct CounterService {
+	count: AtomicU64,
+}
+
+impl JunoService for CounterService {
+	fn prefix(&self) -> &str {
+		"counter"
+	}
+
+	fn declare_functions(functions: &mut ServiceFunctions<Self>) {
+		functions
+			.function("increment", |service, _, _| async move {
+				Ok(json!(service.count.fetch_add(1, Ordering::SeqCst) + 1).into())
+			})
+			.function("get", |service, _, _| async move {
+				Ok(json!(service.count.load(Ordering::SeqCst)).into())
+			});
+	}
+}
+
+#[test]
+fn should_declare_service_functions_under_its_prefix() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-23.sock").await;
+
+		let service = Arc::new(CounterService {
+			count: AtomicU64::new(0),
+		});
+		let (registered, declared) = future::join(module.register_service(service), async {
+			vec![router.respond(10).await, router.respond(10).await]
+		})
+		.await;
+		registered.unwrap();
+		assert_eq!(declared[0]["function"], "counter.increment");
+		assert_eq!(declared[1]["function"], "counter.get");
+
+		let mut responses = vec![];
+		for (request_id, function) in [
+			("caller-1", "counter.increment"),
+			("caller-2", "counter.increment"),
+			("caller-3", "counter.get"),
+		] {
+			router
+				.write_message(json!({
+					"requestId": request_id,
+					"type": 3,
+					"function": function,
+					"arguments": {},
+				}))
+				.await;
+			responses.push(router.read_message().await);
+		}
+		assert_eq!(responses[0]["data"], 1);
+		assert_eq!(responses[1]["data"], 2);
+		assert_eq!(responses[2]["data"], 2);
+	});
+}
+
+#[juno::function]
+async fn divide(dividend: i64, divisor: Option<i64>) -> Result<i64, Error> {
+	match divisor {
+		Some(0) => Err(Error::from_module(1, "Division by zero")),
+		Some(divisor) => Ok(dividend / divisor),
+		None => Ok(dividend),
+	}
+}
+
+#[juno::hook]
+fn on_order_created(_order: OrderCreated) {}
+
+#[test]
+fn should_declare_macro_generated_functions_and_hooks() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-24.sock").await;
+
+		let (declared, _) = future::join(
+			module.declare_async_function("divide", divide),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+		let (registered, _) = future::join(
+			module.register_async_hook("orders.created", on_order_created),
+			router.respond(6),
+		)
+		.await;
+		registered.unwrap();
+
+		let mut responses = vec![];
+		for arguments in [
+			json!({ "dividend": 10, "divisor": 2 }),
+			json!({ "dividend": 10 }),
+			json!({ "dividend": 10, "divisor": 0 }),
+			json!({ "dividend": "ten" }),
+		] {
+			router
+				.write_message(json!({
+					"requestId": "caller-1",
+					"type": 3,
+					"function": "divide",
+					"arguments": arguments,
+				}))
+				.await;
+			responses.push(router.read_message().await);
+		}
+		assert_eq!(responses[0]["data"], 5);
+		assert_eq!(responses[1]["data"], 10);
+		assert_eq!(responses[2]["type"], 0);
+		assert_eq!(responses[2]["error"], 1);
+		assert_eq!(responses[3]["type"], 0);
+		assert_eq!(responses[3]["error"], 0);
+	});
+}
+
+#[test]
+fn should_reject_calls_over_the_limit_with_their_own_code() {
+	task::block_on(async {
+		let socket_path = "./temp-module-39.sock";
+		let mut module = JunoModule::from_unix_socket(socket_path);
+		module.set_max_running_calls(1).unwrap();
+		let (mut module, mut router) = setup_module_with(module, socket_path, HashMap::new()).await;
+		let result = module.set_max_running_calls(2);
+		assert!(matches!(result, Err(Error::Configuration(_))));
+		let result = module.set_hook_execution(HookExecution::Concurrent);
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		let (declared, _) = future::join(
+			module.declare_async_function("wait", |_, _| future::pending()),
+			router.respond(10),
+		)
+		.await;
+		declared.unwrap();
+
+		for request_id in ["caller-1", "caller-2"].iter() {
+			router
+				.write_message(json!({
+					"requestId": request_id,
+					"type": 3,
+					"function": "wait",
+					"arguments": {},
+				}))
+				.await;
+		}
+		let response = router.read_message().await;
+		assert_eq!(response["requestId"], "caller-2");
+		assert_eq!(response["type"], 0);
+		assert_eq!(response["error"], errors::TOO_MANY_CALLS);
+		assert!(response["message"]
+			.as_str()
+			.unwrap()
+			.contains("At most 1 can run at once"));
+	});
+}
@@ -0,0 +1,444 @@
+use async_std::{prelude::*, task};
+use futures::{channel::mpsc::unbounded, future};
+use juno::{
+	hooks::{HookReplay, HookRetention},
+	json,
+	models::Value,
+	Error, JunoModule,
+};
+use std::{collections::HashMap, time::Duration};
+
+use super::support::{setup_module, setup_module_with, OrderCreated};
+
+#[test]
+fn should_receive_hook_events_from_subscription() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-9.sock").await;
+
+		let (subscription, register) =
+			future::join(module.subscribe("other.updated"), router.respond(6)).await;
+		let mut subscription = subscription.unwrap();
+		assert_eq!(register["type"], 5);
+		assert_eq!(register["hook"], "other.updated");
+
+		for request_id in ["trigger-1", "trigger-2"] {
+			router
+				.write_message(json!({
+					"requestId": request_id,
+					"type": 7,
+					"hook": "other.updated",
+				}))
+				.await;
+		}
+
+		for _ in 0..2 {
+			let event = subscription.next().await.unwrap();
+			assert_eq!(event.hook, "other.updated");
+		}
+	});
+}
+
+#[test]
+fn should_unregister_hook_after_last_listener() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-10.sock").await;
+
+		let (first, register) = future::join(
+			module.register_hook("other.updated", |_| {}),
+			router.respond(6),
+		)
+		.await;
+		assert_eq!(register["type"], 5);
+		// The hook is already registered with juno, so this doesn't send anything
+		let second = module.register_hook("other.updated", |_| {}).await.unwrap();
+
+		first.unwrap().unregister().await.unwrap();
+		let (result, unregister) = future::join(second.unregister(), router.respond(16)).await;
+		result.unwrap();
+		assert_eq!(unregister["type"], 15);
+		assert_eq!(unregister["hook"], "other.updated");
+	});
+}
+
+#[test]
+fn should_unregister_hook_when_subscription_is_dropped() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-11.sock").await;
+
+		let (subscription, _) =
+			future::join(module.subscribe("other.updated"), router.respond(6)).await;
+		drop(subscription.unwrap());
+
+		let unregister = router.read_message().await;
+		assert_eq!(unregister["type"], 15);
+		assert_eq!(unregister["hook"], "other.updated");
+	});
+}
+
+#[test]
+fn should_match_hooks_against_patterns() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-15.sock").await;
+
+		let (orders, _) = future::join(module.subscribe("orders.*"), router.respond(6)).await;
+		let mut orders = orders.unwrap();
+		let (created, _) = future::join(module.subscribe("*.created"), router.respond(6)).await;
+		let mut created = created.unwrap();
+
+		for hook in [
+			"billing.paid",
+			"orders.created",
+			"users.created",
+			"orders.shipped",
+		] {
+			router
+				.write_message(json!({
+					"requestId": hook,
+					"type": 7,
+					"hook": hook,
+				}))
+				.await;
+		}
+
+		assert_eq!(orders.next().await.unwrap().hook, "orders.created");
+		assert_eq!(orders.next().await.unwrap().hook, "orders.shipped");
+		assert_eq!(created.next().await.unwrap().hook, "orders.created");
+		assert_eq!(created.next().await.unwrap().hook, "users.created");
+	});
+}
+
+#[test]
+fn should_isolate_failing_async_hook_listeners() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-16.sock").await;
+
+		let (panicking, _) = future::join(
+			module.register_async_hook("ping", |_| async { panic!("listener panicked") }),
+			router.respond(6),
+		)
+		.await;
+		panicking.unwrap();
+		module
+			.register_async_hook("ping", |_| async { Err(Error::from_module(1, "failed")) })
+			.await
+			.unwrap();
+		let (sender, mut receiver) = unbounded();
+		let count = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+		module
+			.register_async_hook("ping", move |event| {
+				let count = count.clone();
+				let sender = sender.clone();
+				async move {
+					let count = count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+					sender.unbounded_send((event.hook, count)).unwrap();
+					Ok(())
+				}
+			})
+			.await
+			.unwrap();
+
+		for request_id in ["ping-1", "ping-2"] {
+			router
+				.write_message(json!({
+					"requestId": request_id,
+					"type": 7,
+					"hook": "ping",
+				}))
+				.await;
+		}
+
+		assert_eq!(receiver.next().await.unwrap(), (String::from("ping"), 1));
+		assert_eq!(receiver.next().await.unwrap(), (String::from("ping"), 2));
+	});
+}
+
+#[test]
+fn should_deliver_typed_hook_payloads() {
+	task::block_on(async {
+		let socket_path = "./temp-module-17.sock";
+		let mut module = JunoModule::from_unix_socket(socket_path);
+		let (error_sender, mut errors) = unbounded();
+		module
+			.set_hook_error_sink(move |hook, _| {
+				error_sender.unbounded_send(hook.to_string()).unwrap();
+			})
+			.unwrap();
+		let (mut module, mut router) = setup_module_with(module, socket_path, HashMap::new()).await;
+		// The read loop already has its sink
+		let result = module.set_hook_error_sink(|_, _| {});
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		let (sender, mut orders) = unbounded();
+		let (registered, _) = future::join(
+			module.register_typed_hook("orders.created", move |order: OrderCreated| {
+				sender.unbounded_send(order).unwrap();
+			}),
+			router.respond(6),
+		)
+		.await;
+		registered.unwrap();
+
+		let order = OrderCreated {
+			id: 1,
+			customer: String::from("customer"),
+		};
+		let (triggered, request) = future::join(
+			module.trigger_typed_hook("orders.created", &order),
+			router.respond(8),
+		)
+		.await;
+		triggered.unwrap();
+		assert_eq!(request["data"], json!({ "id": 1, "customer": "customer" }));
+
+		router
+			.write_message(json!({
+				"requestId": "trigger-1",
+				"type": 7,
+				"hook": "orders.created",
+				"data": { "id": "not-a-number" },
+			}))
+			.await;
+		router
+			.write_message(json!({
+				"requestId": "trigger-2",
+				"type": 7,
+				"hook": "orders.created",
+				"data": request["data"],
+			}))
+			.await;
+
+		assert_eq!(errors.next().await.unwrap(), "orders.created");
+		assert_eq!(orders.next().await.unwrap(), order);
+	});
+}
+
+#[test]
+fn should_replay_retained_hooks_to_late_subscribers() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-18.sock").await;
+
+		let (retained, _) = future::join(
+			module.retain_hook("config.*", HookRetention::new(2)),
+			router.respond(6),
+		)
+		.await;
+		retained.unwrap();
+		// The retention already registered the hook with juno
+		let mut early = module.subscribe("config.*").await.unwrap();
+
+		for (index, hook) in ["config.a", "config.b", "config.a"].iter().enumerate() {
+			router
+				.write_message(json!({
+					"requestId": format!("trigger-{}", index),
+					"type": 7,
+					"hook": hook,
+					"data": index,
+				}))
+				.await;
+		}
+		for _ in 0..3 {
+			early.next().await.unwrap();
+		}
+
+		let mut last = module
+			.subscribe_with_replay("config.*", HookReplay::Last(2))
+			.await
+			.unwrap();
+		let event = last.next().await.unwrap();
+		assert_eq!(
+			(event.hook.as_str(), event.data),
+			("config.b", json!(1).into())
+		);
+		let event = last.next().await.unwrap();
+		assert_eq!(
+			(event.hook.as_str(), event.data),
+			("config.a", json!(2).into())
+		);
+
+		let (latest, _) = future::join(
+			module.subscribe_with_replay("config.a", HookReplay::Latest),
+			router.respond(6),
+		)
+		.await;
+		let event = latest.unwrap().next().await.unwrap();
+		assert_eq!(
+			(event.hook.as_str(), event.data),
+			("config.a", json!(2).into())
+		);
+	});
+}
+
+#[test]
+fn should_stop_replaying_retained_hooks_past_their_ttl() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-47.sock").await;
+
+		let (retained, _) = future::join(
+			module.retain_hook(
+				"config.a",
+				HookRetention::latest().with_ttl(Duration::from_millis(50)),
+			),
+			router.respond(6),
+		)
+		.await;
+		retained.unwrap();
+		let mut early = module.subscribe("config.a").await.unwrap();
+
+		let trigger = |data: u64| {
+			json!({
+				"requestId": format!("trigger-{}", data),
+				"type": 7,
+				"hook": "config.a",
+				"data": data,
+			})
+		};
+		router.write_message(trigger(1)).await;
+		early.next().await.unwrap();
+		let mut fresh = module
+			.subscribe_with_replay("config.a", HookReplay::Latest)
+			.await
+			.unwrap();
+		assert_eq!(fresh.next().await.unwrap().data, json!(1).into());
+
+		task::sleep(Duration::from_millis(80)).await;
+		let mut latest = module
+			.subscribe_with_replay("config.a", HookReplay::Latest)
+			.await
+			.unwrap();
+		let mut all = module
+			.subscribe_with_replay("config.a", HookReplay::All)
+			.await
+			.unwrap();
+		// Nothing is replayed, so the first event they get is the next one triggered
+		router.write_message(trigger(2)).await;
+		assert_eq!(latest.next().await.unwrap().data, json!(2).into());
+		assert_eq!(all.next().await.unwrap().data, json!(2).into());
+	});
+}
+
+#[test]
+fn should_trigger_scheduled_hooks_until_cancelled() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-19.sock").await;
+
+		let scheduled = module
+			.schedule_hook("tick", Duration::from_millis(20), json!(1).into())
+			.unwrap();
+		for _ in 0..2 {
+			let request = router.respond(8).await;
+			assert_eq!(request["hook"], "tick");
+			assert_eq!(request["data"], 1);
+		}
+		scheduled.cancel();
+
+		module
+			.trigger_hook_at(
+				"reminder",
+				std::time::Instant::now() + Duration::from_millis(20),
+				Value::Null,
+			)
+			.unwrap();
+		let request = router.respond(8).await;
+		assert_eq!(request["hook"], "reminder");
+
+		let result = module.schedule_hook_cron("tick", "not a cron expression", Value::Null);
+		assert!(matches!(result, Err(Error::Internal(_))));
+	});
+}
+
+#[test]
+fn should_register_hook_again_only_after_juno_unregistered_it() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-35.sock").await;
+
+		let (subscription, _) =
+			future::join(module.subscribe("other.updated"), router.respond(6)).await;
+		drop(subscription.unwrap());
+
+		let router = async {
+			let unregister = router.read_message().await;
+			assert_eq!(unregister["type"], 15);
+			// Nothing else is sent while juno hasn't answered the unregistration
+			let early =
+				async_std::future::timeout(Duration::from_millis(50), router.read_message()).await;
+			assert!(early.is_err());
+			let mut response = unregister.clone();
+			response["type"] = json!(16);
+			router.write_message(response).await;
+
+			router.respond(6).await
+		};
+		// Give the dropped subscription a head start on unregistering
+		let subscribe = async {
+			task::sleep(Duration::from_millis(10)).await;
+			module.subscribe("other.updated").await
+		};
+		let (register, subscription) = future::join(router, subscribe).await;
+		subscription.unwrap();
+		assert_eq!(register["type"], 5);
+		assert_eq!(register["hook"], "other.updated");
+	});
+}
+
+#[test]
+fn should_retain_hooks_triggered_before_anyone_subscribed() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-36.sock").await;
+
+		let (retained, register) = future::join(
+			module.retain_hook("config.updated", HookRetention::latest()),
+			router.respond(6),
+		)
+		.await;
+		retained.unwrap();
+		assert_eq!(register["type"], 5);
+		assert_eq!(register["hook"], "config.updated");
+
+		router
+			.write_message(json!({
+				"requestId": "trigger-0",
+				"type": 7,
+				"hook": "config.updated",
+				"data": "v1",
+			}))
+			.await;
+		// Let the module read the hook before anyone subscribes
+		task::sleep(Duration::from_millis(20)).await;
+
+		let mut subscription = module
+			.subscribe_with_replay("config.updated", HookReplay::Latest)
+			.await
+			.unwrap();
+		let event = subscription.next().await.unwrap();
+		assert_eq!(
+			(event.hook.as_str(), event.data),
+			("config.updated", json!("v1").into())
+		);
+		drop(subscription);
+
+		// Once released, nothing keeps the hook registered anymore
+		let (released, unregister) =
+			future::join(module.release_hook("config.updated"), router.respond(16)).await;
+		released.unwrap();
+		assert_eq!(unregister["type"], 15);
+		assert_eq!(unregister["hook"], "config.updated");
+	});
+}
+
+#[test]
+fn should_keep_scheduled_hooks_going_while_juno_is_slow_to_respond() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-37.sock").await;
+
+		let scheduled = module
+			.schedule_hook("tick", Duration::from_millis(20), json!(1).into())
+			.unwrap();
+		// None of the triggers are answered, which mustn't hold up the ones after them
+		for _ in 0..3 {
+			let request = router.read_message().await;
+			assert_eq!(request["type"], 7);
+			assert_eq!(request["hook"], "tick");
+		}
+		scheduled.cancel();
+	});
+}
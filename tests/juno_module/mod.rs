@@ -0,0 +1,6 @@
+mod builder;
+mod calls;
+mod functions;
+mod hooks;
+mod resilience;
+mod support;
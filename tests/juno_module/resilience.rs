@@ -0,0 +1,326 @@
+use async_std::{prelude::*, task};
+use futures::future;
+use juno::{
+	errors,
+	functions::{CircuitBreakerPolicy, CircuitState, ModuleStatus, RetryPolicy},
+	json,
+	models::Value,
+	Error, JunoModule,
+};
+use std::{collections::HashMap, time::Duration};
+
+use super::support::{setup_module, setup_module_with};
+
+#[test]
+fn should_wait_for_module_to_become_available() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-29.sock").await;
+
+		// Juno doesn't know the module at first, then the module answers for itself
+		let probes = async {
+			let mut probes = vec![];
+			for error in [4, 4, 5] {
+				let probe = router.read_message().await;
+				router
+					.write_message(json!({
+						"type": 0,
+						"requestId": probe["requestId"],
+						"error": error,
+					}))
+					.await;
+				probes.push(probe);
+			}
+			probes
+		};
+		let (probes, result) = future::join(
+			probes,
+			module.wait_for_module("other", Duration::from_secs(5)),
+		)
+		.await;
+		result.unwrap();
+		assert!(probes
+			.iter()
+			.all(|probe| probe["function"].as_str().unwrap().starts_with("other.")));
+
+		// Only changes are reported
+		let mut availability = module
+			.module_availability("other", Duration::from_millis(10))
+			.unwrap();
+		let probes = async {
+			for error in [5, 5, 4] {
+				let probe = router.read_message().await;
+				router
+					.write_message(json!({
+						"type": 0,
+						"requestId": probe["requestId"],
+						"error": error,
+					}))
+					.await;
+			}
+		};
+		let statuses = async {
+			vec![
+				availability.next().await.unwrap(),
+				availability.next().await.unwrap(),
+			]
+		};
+		let (_, statuses) = future::join(probes, statuses).await;
+		assert_eq!(
+			statuses,
+			vec![ModuleStatus::Available, ModuleStatus::Unavailable]
+		);
+	});
+}
+
+#[test]
+fn should_retry_calls_according_to_policy() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-30.sock").await;
+		let policy = RetryPolicy::exponential(3, Duration::from_millis(10));
+
+		// The module isn't registered yet on the first attempt
+		let responses = async {
+			let first = router.read_message().await;
+			router
+				.write_message(json!({
+					"type": 0,
+					"requestId": first["requestId"],
+					"error": 4,
+				}))
+				.await;
+			let second = router.read_message().await;
+			router
+				.write_message(json!({
+					"type": 4,
+					"requestId": second["requestId"],
+					"data": "done",
+				}))
+				.await;
+			(first, second)
+		};
+		let ((first, second), result) = future::join(
+			responses,
+			module.call_function_with_retry("other.function", HashMap::new(), &policy),
+		)
+		.await;
+		assert_eq!(result.unwrap(), Value::String(String::from("done")));
+		assert_ne!(first["requestId"], second["requestId"]);
+
+		// Errors from the function itself are only retried if it's idempotent
+		let failure = json!({ "type": 0, "error": 1, "message": "Failed", "source": "module" });
+		let response = async {
+			let request = router.read_message().await;
+			let mut response = failure.clone();
+			response["requestId"] = request["requestId"].clone();
+			router.write_message(response).await;
+		};
+		let (_, result) = future::join(
+			response,
+			module.call_function_with_retry("other.function", HashMap::new(), &policy),
+		)
+		.await;
+		assert!(matches!(result, Err(Error::FromModule { code: 1, .. })));
+
+		let responses = async {
+			for _ in 0..3 {
+				let request = router.read_message().await;
+				let mut response = failure.clone();
+				response["requestId"] = request["requestId"].clone();
+				router.write_message(response).await;
+			}
+		};
+		let (_, result) = future::join(
+			responses,
+			module.call_function_with_retry("other.function", HashMap::new(), &policy.idempotent()),
+		)
+		.await;
+		assert!(matches!(result, Err(Error::FromModule { code: 1, .. })));
+	});
+}
+
+#[test]
+fn should_fail_fast_while_circuit_is_open() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-31.sock").await;
+		module.set_call_timeout(Duration::from_secs(5));
+		module
+			.set_circuit_breaker(CircuitBreakerPolicy::new(2, Duration::from_millis(50)))
+			.unwrap();
+		let mut changes = module.circuit_state_changes().await.unwrap();
+
+		for _ in 0..2 {
+			let response = async {
+				let request = router.read_message().await;
+				router
+					.write_message(json!({
+						"type": 0,
+						"requestId": request["requestId"],
+						"error": 4,
+					}))
+					.await;
+			};
+			let (_, result) = future::join(
+				response,
+				module.call_function("other.function", HashMap::new()),
+			)
+			.await;
+			assert!(matches!(result, Err(Error::FromJuno(4))));
+		}
+		assert_eq!(
+			module.circuit_state("other").await,
+			Some(CircuitState::Open)
+		);
+		assert_eq!(
+			module.circuit_state("another").await,
+			Some(CircuitState::Closed)
+		);
+
+		// Nothing is sent to juno while the circuit is open
+		let result = module.call_function("other.function", HashMap::new()).await;
+		assert!(matches!(result, Err(Error::CircuitOpen(module_id)) if module_id == "other"));
+
+		task::sleep(Duration::from_millis(60)).await;
+		let response = async {
+			let request = router.read_message().await;
+			router
+				.write_message(json!({
+					"type": 4,
+					"requestId": request["requestId"],
+					"data": "done",
+				}))
+				.await;
+		};
+		let (_, result) = future::join(
+			response,
+			module.call_function("other.function", HashMap::new()),
+		)
+		.await;
+		result.unwrap();
+
+		let mut states = vec![];
+		for _ in 0..3 {
+			states.push(changes.next().await.unwrap().state);
+		}
+		assert_eq!(
+			states,
+			vec![
+				CircuitState::Open,
+				CircuitState::HalfOpen,
+				CircuitState::Closed
+			]
+		);
+	});
+}
+
+#[test]
+fn should_keep_circuit_closed_when_module_lacks_the_function() {
+	task::block_on(async {
+		let (mut module, mut router) = setup_module("./temp-module-41.sock").await;
+		module.set_call_timeout(Duration::from_secs(5));
+		module
+			.set_circuit_breaker(CircuitBreakerPolicy::new(1, Duration::from_secs(10)))
+			.unwrap();
+
+		// The module is up, it just doesn't declare the function
+		for _ in 0..2 {
+			let response = async {
+				let request = router.read_message().await;
+				router
+					.write_message(json!({
+						"type": 0,
+						"requestId": request["requestId"],
+						"error": errors::UNKNOWN_FUNCTION,
+					}))
+					.await;
+			};
+			let (_, result) = future::join(
+				response,
+				module.call_function("other.missing", HashMap::new()),
+			)
+			.await;
+			assert!(matches!(
+				result,
+				Err(Error::FromJuno(errors::UNKNOWN_FUNCTION))
+			));
+			assert_eq!(
+				module.circuit_state("other").await,
+				Some(CircuitState::Closed)
+			);
+		}
+	});
+}
+
+#[test]
+fn should_stop_probing_modules_once_the_connection_closes() {
+	task::block_on(async {
+		let (module, mut router) = setup_module("./temp-module-44.sock").await;
+
+		let mut availability = module
+			.module_availability("other", Duration::from_millis(20))
+			.unwrap();
+		let probe = router.read_message().await;
+		router
+			.write_message(json!({
+				"type": 0,
+				"requestId": probe["requestId"],
+				"error": errors::UNKNOWN_FUNCTION,
+			}))
+			.await;
+		assert_eq!(availability.next().await, Some(ModuleStatus::Available));
+
+		drop(router);
+		assert_eq!(availability.next().await, Some(ModuleStatus::Unavailable));
+		assert_eq!(availability.next().await, None);
+	});
+}
+
+#[test]
+fn should_open_circuit_when_module_stops_responding() {
+	task::block_on(async {
+		let mut module = JunoModule::from_unix_socket("./temp-module-45.sock");
+		let policy = CircuitBreakerPolicy::new(2, Duration::from_secs(10));
+		let result = module.set_circuit_breaker(policy);
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		let result = JunoModule::builder()
+			.unix_socket("./temp-module-45.sock")
+			.module_id("test")
+			.version("1.0.0")
+			.circuit_breaker(policy)
+			.build()
+			.await;
+		assert!(matches!(result, Err(Error::Configuration(_))));
+
+		module.set_call_timeout(Duration::from_millis(20));
+		module.set_circuit_breaker(policy).unwrap();
+		let (module, mut router) =
+			setup_module_with(module, "./temp-module-45.sock", HashMap::new()).await;
+
+		// The calls reach juno, but nothing ever responds to them
+		for _ in 0..2 {
+			let request = async {
+				// Skips the cancel sent for the call that timed out before
+				loop {
+					let request = router.read_message().await;
+					if request["function"].is_string() {
+						break request;
+					}
+				}
+			};
+			let (request, result) = future::join(
+				request,
+				module.call_function("other.function", HashMap::new()),
+			)
+			.await;
+			assert_eq!(request["function"], "other.function");
+			assert!(matches!(result, Err(Error::Timeout)));
+		}
+		assert_eq!(
+			module.circuit_state("other").await,
+			Some(CircuitState::Open)
+		);
+
+		let result = module.call_function("other.function", HashMap::new()).await;
+		assert!(matches!(result, Err(Error::CircuitOpen(module_id)) if module_id == "other"));
+	});
+}
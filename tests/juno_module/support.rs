@@ -0,0 +1,113 @@
+use async_std::{
+	fs::remove_file,
+	io::{BufReader, Lines},
+	os::unix::net::{UnixListener, UnixStream},
+	prelude::*,
+};
+use futures::future;
+use juno::{json, JunoModule};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::{collections::HashMap, future::Future};
+
+// Pretends to be the juno router on the other end of the module's socket
+pub(super) struct FakeRouter {
+	pub(super) stream: UnixStream,
+	pub(super) lines: Lines<BufReader<UnixStream>>,
+}
+
+impl FakeRouter {
+	pub(super) async fn read_message(&mut self) -> JsonValue {
+		let line = self.lines.next().await.unwrap().unwrap();
+		serde_json::from_str(&line).unwrap()
+	}
+
+	pub(super) async fn write_message(&mut self, message: JsonValue) {
+		self.stream
+			.write_all(format!("{}\n", message).as_bytes())
+			.await
+			.unwrap();
+	}
+
+	// Responds to the next request by echoing it back with the response type
+	pub(super) async fn respond(&mut self, response_type: u64) -> JsonValue {
+		let message = self.read_message().await;
+		let mut response = message.clone();
+		response["type"] = json!(response_type);
+		self.write_message(response).await;
+		message
+	}
+
+	// Accepts the listener a registered module puts on the discovery hook, and its announcement
+	pub(super) async fn accept_discovery(&mut self) {
+		let register_hook = self.respond(6).await;
+		assert_eq!(register_hook["hook"], "__juno.modules");
+		let announcement = self.respond(8).await;
+		assert_eq!(announcement["hook"], "__juno.modules");
+	}
+}
+
+// Echoes the registration back as accepted
+pub(super) fn accept_registration(registration: &JsonValue) -> JsonValue {
+	let mut response = registration.clone();
+	response["type"] = json!(2);
+	response
+}
+
+// Listens on the socket while the module connects, and answers its registration with whatever
+// `respond` makes of it. Returns what connecting resolved to, the router, and the registration
+pub(super) async fn connect_module<T>(
+	socket_path: &str,
+	connect: impl Future<Output = T>,
+	respond: impl FnOnce(&JsonValue) -> JsonValue,
+) -> (T, FakeRouter, JsonValue) {
+	let _ = remove_file(socket_path).await;
+	let listener = UnixListener::bind(socket_path).await.unwrap();
+	let router = async {
+		let (stream, _) = listener.accept().await.unwrap();
+		let mut router = FakeRouter {
+			stream: stream.clone(),
+			lines: BufReader::new(stream).lines(),
+		};
+		let registration = router.read_message().await;
+		let response = respond(&registration);
+		router.write_message(response.clone()).await;
+		if response["type"] == 2 {
+			router.accept_discovery().await;
+		}
+		(router, registration)
+	};
+	let ((router, registration), result) = future::join(router, connect).await;
+
+	drop(listener);
+	remove_file(socket_path).await.unwrap();
+	(result, router, registration)
+}
+
+pub(super) async fn setup_module(socket_path: &str) -> (JunoModule, FakeRouter) {
+	setup_module_with(
+		JunoModule::from_unix_socket(socket_path),
+		socket_path,
+		HashMap::new(),
+	)
+	.await
+}
+
+// Same as setup_module, for a module that's already configured, or that has dependencies
+pub(super) async fn setup_module_with(
+	mut module: JunoModule,
+	socket_path: &str,
+	dependencies: HashMap<String, String>,
+) -> (JunoModule, FakeRouter) {
+	let initialize = module.initialize("test", "1.0.0", dependencies);
+	let (result, router, _) = connect_module(socket_path, initialize, accept_registration).await;
+	result.unwrap();
+	(module, router)
+}
+
+// The payload of the typed hooks the tests trigger
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(super) struct OrderCreated {
+	pub(super) id: u64,
+	pub(super) customer: String,
+}
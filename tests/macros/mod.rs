@@ -0,0 +1,6 @@
+// The attributes refuse what they can't turn into a handler, with an error pointing at why
+#[test]
+fn should_reject_functions_the_macros_cannot_handle() {
+	let cases = trybuild::TestCases::new();
+	cases.compile_fail("tests/macros/ui/*.rs");
+}
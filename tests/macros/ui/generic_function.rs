@@ -0,0 +1,6 @@
+#[juno::function]
+async fn echo<T: ToString>(value: T) -> String {
+	value.to_string()
+}
+
+fn main() {}
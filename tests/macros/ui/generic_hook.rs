@@ -0,0 +1,4 @@
+#[juno::hook]
+fn on_updated<T>(_payload: T) {}
+
+fn main() {}
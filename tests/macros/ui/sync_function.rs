@@ -0,0 +1,6 @@
+#[juno::function]
+fn add(a: i64, b: i64) -> i64 {
+	a + b
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[juno::function]
+async fn echo(value: String) -> String
+where
+	String: Clone,
+{
+	value
+}
+
+fn main() {}
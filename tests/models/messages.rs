@@ -1,4 +1,4 @@
-use juno::models::{BaseMessage, Value};
+use juno::models::{BaseMessage, ErrorSource, Value};
 use std::collections::HashMap;
 
 #[test]
@@ -32,6 +32,7 @@ fn check_types_are_storing_values() {
 		BaseMessage::TriggerHookRequest {
 			request_id: String::from("request_id"),
 			hook: String::from("hook"),
+			data: Value::Null,
 		},
 		BaseMessage::TriggerHookResponse {
 			request_id: String::from("request_id"),
@@ -44,9 +45,42 @@ fn check_types_are_storing_values() {
 			request_id: String::from("request_id"),
 			function: String::from("function"),
 		},
+		BaseMessage::CancelFunctionCallRequest {
+			request_id: String::from("request_id"),
+		},
+		BaseMessage::FunctionCallStreamChunk {
+			request_id: String::from("request_id"),
+			data: Value::Null,
+		},
+		BaseMessage::FunctionCallStreamEnd {
+			request_id: String::from("request_id"),
+		},
+		BaseMessage::FunctionCallProgress {
+			request_id: String::from("request_id"),
+			progress: 50.0,
+			message: None,
+		},
+		BaseMessage::UnregisterHookRequest {
+			request_id: String::from("request_id"),
+			hook: String::from("hook"),
+		},
+		BaseMessage::UnregisterHookResponse {
+			request_id: String::from("request_id"),
+		},
+		BaseMessage::UndeclareFunctionRequest {
+			request_id: String::from("request_id"),
+			function: String::from("function"),
+		},
+		BaseMessage::UndeclareFunctionResponse {
+			request_id: String::from("request_id"),
+			function: String::from("function"),
+		},
 		BaseMessage::Error {
 			request_id: String::from("request_id"),
 			error: 0,
+			message: Some(String::from("message")),
+			details: Value::Null,
+			source: ErrorSource::Module,
 		},
 		BaseMessage::Unknown {
 			request_id: String::from("request_id"),
@@ -89,9 +123,14 @@ fn check_types_are_storing_values() {
 			BaseMessage::RegisterHookResponse { request_id } => {
 				assert_eq!(request_id, &String::from("request_id"));
 			}
-			BaseMessage::TriggerHookRequest { request_id, hook } => {
+			BaseMessage::TriggerHookRequest {
+				request_id,
+				hook,
+				data,
+			} => {
 				assert_eq!(request_id, &String::from("request_id"));
 				assert_eq!(hook, &String::from("hook"));
+				assert_eq!(data, &Value::Null);
 			}
 			BaseMessage::TriggerHookResponse { request_id } => {
 				assert_eq!(request_id, &String::from("request_id"));
@@ -110,9 +149,58 @@ fn check_types_are_storing_values() {
 				assert_eq!(request_id, &String::from("request_id"));
 				assert_eq!(function, &String::from("function"));
 			}
-			BaseMessage::Error { request_id, error } => {
+			BaseMessage::CancelFunctionCallRequest { request_id } => {
+				assert_eq!(request_id, &String::from("request_id"));
+			}
+			BaseMessage::FunctionCallStreamChunk { request_id, data } => {
+				assert_eq!(request_id, &String::from("request_id"));
+				assert_eq!(data, &Value::Null);
+			}
+			BaseMessage::FunctionCallStreamEnd { request_id } => {
+				assert_eq!(request_id, &String::from("request_id"));
+			}
+			BaseMessage::FunctionCallProgress {
+				request_id,
+				progress,
+				message,
+			} => {
+				assert_eq!(request_id, &String::from("request_id"));
+				assert_eq!(progress, &50.0);
+				assert_eq!(message, &None);
+			}
+			BaseMessage::UnregisterHookRequest { request_id, hook } => {
+				assert_eq!(request_id, &String::from("request_id"));
+				assert_eq!(hook, &String::from("hook"));
+			}
+			BaseMessage::UnregisterHookResponse { request_id } => {
+				assert_eq!(request_id, &String::from("request_id"));
+			}
+			BaseMessage::UndeclareFunctionRequest {
+				request_id,
+				function,
+			} => {
+				assert_eq!(request_id, &String::from("request_id"));
+				assert_eq!(function, &String::from("function"));
+			}
+			BaseMessage::UndeclareFunctionResponse {
+				request_id,
+				function,
+			} => {
+				assert_eq!(request_id, &String::from("request_id"));
+				assert_eq!(function, &String::from("function"));
+			}
+			BaseMessage::Error {
+				request_id,
+				error,
+				message,
+				details,
+				source,
+			} => {
 				assert_eq!(request_id, &String::from("request_id"));
 				assert_eq!(error, &0);
+				assert_eq!(message, &Some(String::from("message")));
+				assert_eq!(details, &Value::Null);
+				assert_eq!(source, &ErrorSource::Module);
 			}
 			BaseMessage::Unknown { request_id } => {
 				assert_eq!(request_id, &String::from("request_id"));
@@ -0,0 +1,134 @@
+use juno::{
+	models::{BaseMessage, ErrorSource, Value},
+	protocol::BaseProtocol,
+};
+use std::collections::HashMap;
+
+#[test]
+fn should_encode_and_decode_error_with_message_and_details() {
+	let protocol = BaseProtocol::default();
+	let mut details = HashMap::new();
+	details.insert(String::from("field"), Value::String(String::from("name")));
+
+	let encoded = protocol.encode(BaseMessage::Error {
+		request_id: String::from("request_id"),
+		error: 42,
+		message: Some(String::from("Invalid name")),
+		details: Value::Object(details.clone()),
+		source: ErrorSource::Module,
+	});
+	// Strip the trailing newline, like the connection does
+	let decoded = protocol.decode(&encoded[..encoded.len() - 1]);
+
+	if let BaseMessage::Error {
+		request_id,
+		error,
+		message,
+		details: decoded_details,
+		source,
+	} = decoded
+	{
+		assert_eq!(request_id, String::from("request_id"));
+		assert_eq!(error, 42);
+		assert_eq!(message, Some(String::from("Invalid name")));
+		assert_eq!(decoded_details, Value::Object(details));
+		assert_eq!(source, ErrorSource::Module);
+	} else {
+		panic!("Decoded message was not an error");
+	}
+}
+
+#[test]
+fn should_decode_error_without_message_and_details() {
+	let protocol = BaseProtocol::default();
+	let decoded = protocol.decode(b"{\"requestId\":\"request_id\",\"type\":0,\"error\":5}");
+
+	if let BaseMessage::Error {
+		request_id,
+		error,
+		message,
+		details,
+		source,
+	} = decoded
+	{
+		assert_eq!(request_id, String::from("request_id"));
+		assert_eq!(error, 5);
+		assert_eq!(message, None);
+		assert_eq!(details, Value::Null);
+		// Juno's own errors don't say where they came from
+		assert_eq!(source, ErrorSource::Juno);
+	} else {
+		panic!("Decoded message was not an error");
+	}
+}
+
+#[test]
+fn should_encode_and_decode_cancel_function_call_request() {
+	let protocol = BaseProtocol::default();
+
+	let encoded = protocol.encode(protocol.cancel_function_call(String::from("request_id")));
+	let decoded = protocol.decode(&encoded[..encoded.len() - 1]);
+
+	assert_eq!(decoded.get_type(), 11);
+	assert_eq!(decoded.get_request_id(), &String::from("request_id"));
+}
+
+#[test]
+fn should_encode_and_decode_function_call_stream_chunk() {
+	let protocol = BaseProtocol::default();
+
+	let encoded = protocol.encode(BaseMessage::FunctionCallStreamChunk {
+		request_id: String::from("request_id"),
+		data: Value::String(String::from("chunk")),
+	});
+	let decoded = protocol.decode(&encoded[..encoded.len() - 1]);
+
+	if let BaseMessage::FunctionCallStreamChunk { request_id, data } = decoded {
+		assert_eq!(request_id, String::from("request_id"));
+		assert_eq!(data, Value::String(String::from("chunk")));
+	} else {
+		panic!("Decoded message was not a stream chunk");
+	}
+}
+
+#[test]
+fn should_encode_and_decode_function_call_progress() {
+	let protocol = BaseProtocol::default();
+
+	let encoded = protocol.encode(BaseMessage::FunctionCallProgress {
+		request_id: String::from("request_id"),
+		progress: 42.5,
+		message: Some(String::from("Halfway there")),
+	});
+	let decoded = protocol.decode(&encoded[..encoded.len() - 1]);
+
+	if let BaseMessage::FunctionCallProgress {
+		request_id,
+		progress,
+		message,
+	} = decoded
+	{
+		assert_eq!(request_id, String::from("request_id"));
+		assert_eq!(progress, 42.5);
+		assert_eq!(message, Some(String::from("Halfway there")));
+	} else {
+		panic!("Decoded message was not a progress update");
+	}
+}
+
+#[test]
+fn should_encode_and_decode_hook_payload() {
+	let protocol = BaseProtocol::default();
+
+	let encoded = protocol.encode(
+		protocol.trigger_hook(String::from("hook"), Value::String(String::from("payload"))),
+	);
+	let decoded = protocol.decode(&encoded[..encoded.len() - 1]);
+
+	if let BaseMessage::TriggerHookRequest { hook, data, .. } = decoded {
+		assert_eq!(hook, String::from("hook"));
+		assert_eq!(data, Value::String(String::from("payload")));
+	} else {
+		panic!("Decoded message was not a hook trigger");
+	}
+}
@@ -1 +1 @@
-
+pub mod json_protocol;
@@ -1,3 +1,6 @@
 mod connection;
+#[cfg(target_family = "unix")]
+mod juno_module;
+mod macros;
 mod models;
 mod protocol;